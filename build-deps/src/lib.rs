@@ -20,12 +20,18 @@
 //! Note: The cargo application ist storing the build-script-output in the build directory,
 //!       for example: `target/debug/build/*/output`.
 extern crate glob;
+extern crate test_generator_common;
+
 use std::path::{Path};
 
-use self::glob::{glob, Paths};
+use self::glob::{glob_with, MatchOptions, Paths, Pattern};
+use self::test_generator_common::env::{self, VariableError};
+#[cfg(feature = "gitignore")]
+use self::test_generator_common::gitignore::IgnoreFiles;
 
 /// Error cases
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Invalid GLOB pattern
     InvalidGlobPattern(String),
@@ -35,6 +41,9 @@ pub enum Error {
 
     /// Expanded pattern contains a path that is no file
     ExpandedPathExpectedFile(String),
+
+    /// The pattern refers to an environment variable not being defined
+    UndefinedVariable(String),
 }
 
 /// Specify a file or directory which, if changed, should trigger a rebuild.
@@ -44,6 +53,27 @@ fn rerun_if_changed<P: AsRef<Path>>(path: P) {
     println!("cargo:rerun-if-changed={}", path.as_ref().display());
 }
 
+/// Specify an environment variable which, if changed, should trigger a rebuild.
+fn rerun_if_env_changed(name: &str) {
+    println!("cargo:rerun-if-env-changed={}", name);
+}
+
+/// Expand the environment variables `${NAME}` of the pattern, such as `${OUT_DIR}`, the same
+/// way the macros of crate `test-generator` do; the values are escaped, as they might contain
+/// GLOB meta-characters. A change of the variables triggers a rebuild.
+fn expand_variables(pattern: &str) -> Result<String, Error> {
+    let expanded = env::expand_variables(pattern, Pattern::escape).map_err(|err| match err {
+        VariableError::Unterminated => {
+            Error::InvalidGlobPattern(format!("unterminated variable in {}", pattern))
+        }
+        VariableError::Undefined(name) => Error::UndefinedVariable(name),
+    })?;
+    for name in &expanded.variables {
+        rerun_if_env_changed(name);
+    }
+    Ok(expanded.pattern)
+}
+
 /// Exapanding the GLOB pattern and adding dependency to Cargo-build-process
 ///
 /// For example:
@@ -60,6 +90,10 @@ fn rerun_if_changed<P: AsRef<Path>>(path: P) {
 ///
 /// Add directories, if the build-process shall be rerun in case of _new_/_removed_ files.
 ///
+/// Environment variables are expanded, such as `"${OUT_DIR}/fixtures/*"` or
+/// `"${CARGO_MANIFEST_DIR}/res/*"`; an undefined variable is returned as
+/// `Error::UndefinedVariable(String)`. A change of the variables triggers a rebuild as well.
+///
 /// ```
 /// // declared in Cargo.toml as "[build-dependencies]"
 /// extern crate build_deps;
@@ -75,14 +109,106 @@ fn rerun_if_changed<P: AsRef<Path>>(path: P) {
 /// ```
 ///
 pub fn rerun_if_changed_paths(pattern: &str) -> Result<(), Error> {
-    let paths: Paths = glob(&pattern).map_err(|err| Error::InvalidGlobPattern(err.to_string()))?;
+    RerunIfChanged::new().paths(pattern)
+}
+
+/// Builder expanding GLOB patterns with options other than the defaults of
+/// `rerun_if_changed_paths`, matching case-insensitively or skipping hidden files and files
+/// ignored by `.gitignore` for example; skipping ignored files requires the optional feature
+/// `gitignore`.
+///
+/// ```
+/// // declared in Cargo.toml as "[build-dependencies]"
+/// extern crate build_deps;
+///
+/// fn main() {
+///    // Skip hidden files such as "res/.cache", and match "res/*.TXT" as well
+///    build_deps::RerunIfChanged::new()
+///        .case_sensitive(false)
+///        .require_literal_leading_dot(true)
+///        .paths("res/*.txt")
+///        .unwrap();
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RerunIfChanged {
+    options: MatchOptions,
+    respect_gitignore: bool,
+}
 
-    for entry in paths {
-        match entry {
-            Ok(path) => rerun_if_changed(&path),
-            Err(e) => return Err(Error::InvalidGlobPattern(e.to_string())),
+/// The derived default of `MatchOptions` would match case-insensitively, differing from `glob`
+impl Default for RerunIfChanged {
+    fn default() -> Self {
+        RerunIfChanged::new()
+    }
+}
+
+impl RerunIfChanged {
+    /// Builder with the default options, the same as `rerun_if_changed_paths`
+    pub fn new() -> RerunIfChanged {
+        RerunIfChanged {
+            options: MatchOptions::new(),
+            respect_gitignore: false,
         }
     }
 
-    Ok(())
+    /// Match upper-case and lower-case characters alike, if `false`; default is `true`
+    pub fn case_sensitive(mut self, value: bool) -> RerunIfChanged {
+        self.options.case_sensitive = value;
+        self
+    }
+
+    /// Require the path separator `/` to be matched literally, not by wildcards such as `*`,
+    /// if `true`; default is `false`
+    pub fn require_literal_separator(mut self, value: bool) -> RerunIfChanged {
+        self.options.require_literal_separator = value;
+        self
+    }
+
+    /// Require a leading dot of hidden files and directories to be matched literally, not by
+    /// wildcards such as `*`, if `true`; default is `false`
+    pub fn require_literal_leading_dot(mut self, value: bool) -> RerunIfChanged {
+        self.options.require_literal_leading_dot = value;
+        self
+    }
+
+    /// Skip paths ignored by `.gitignore` and `.ignore` files, such as backups of editors, if
+    /// `true`; default is `false`. Requires the optional feature `gitignore`.
+    #[cfg(feature = "gitignore")]
+    pub fn respect_gitignore(mut self, value: bool) -> RerunIfChanged {
+        self.respect_gitignore = value;
+        self
+    }
+
+    /// Expand the GLOB pattern with these options and add the paths to the Cargo-build-process,
+    /// the same way as `rerun_if_changed_paths`
+    pub fn paths(&self, pattern: &str) -> Result<(), Error> {
+        let pattern = expand_variables(pattern)?;
+        let paths: Paths = glob_with(&pattern, self.options)
+            .map_err(|err| Error::InvalidGlobPattern(err.to_string()))?;
+
+        let mut is_ignored = ignored_filter(self.respect_gitignore);
+        for entry in paths {
+            match entry {
+                Ok(ref path) if is_ignored(path) => {}
+                Ok(path) => rerun_if_changed(&path),
+                Err(e) => return Err(Error::InvalidGlobPattern(e.to_string())),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Predicate of the paths ignored by `.gitignore` and `.ignore` files, if respected
+#[cfg(feature = "gitignore")]
+fn ignored_filter(respect_gitignore: bool) -> impl FnMut(&Path) -> bool {
+    let mut ignore_files = IgnoreFiles::default();
+    move |path| respect_gitignore && ignore_files.is_ignored(path)
+}
+
+/// Predicate of the paths ignored by `.gitignore` files, none without the feature `gitignore`
+#[cfg(not(feature = "gitignore"))]
+fn ignored_filter(_respect_gitignore: bool) -> impl FnMut(&Path) -> bool {
+    |_| false
 }
@@ -18,12 +18,12 @@ mod bench {
     // ```
     // #[test]
     // fn measure_resource_res_set1_input_txt(b: &mut test::Bencher) {
-    //     measure_resource(b, "res/set1/input.txt".into());
+    //     measure_resource(b, "/path/to/example/res/set1/input.txt".into());
     // }
     //
     // #[test]
     // fn measure_resource_res_set2_input_txt(b: &mut test::Bencher) {
-    //     measure_resource(b, "res/set2/input.txt".into());
+    //     measure_resource(b, "/path/to/example/res/set2/input.txt".into());
     // }
     // ```
     #[bench_resources("res/*/input.txt")]
@@ -31,4 +31,10 @@ mod bench {
         let path = std::path::Path::new(resource);
         b.iter(|| path.exists());
     }
+
+    // A bench for each value, such as `measure_value_0000000010`
+    #[bench_values(10, 100, 1000)]
+    fn measure_value(b: &mut test::Bencher, size: usize) {
+        b.iter(|| vec![0u8; size]);
+    }
 }
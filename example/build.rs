@@ -6,11 +6,25 @@
 // declared in Cargo.toml as "[build-dependencies]"
 extern crate build_deps;
 
+use std::path::PathBuf;
+
 fn main() {
-    // Enumerate files in sub-folder "res/*", being relevant for the test-generation (as example)
-    // If function returns with error, exit with error message.
-    build_deps::rerun_if_changed_paths( "res/*/*" ).unwrap();
+    // Modified resources are detected by the macros themselves. Enumerating the files in
+    // sub-folder "res/*" is kept as example for other consumers of these files.
+    // If function returns with error, exit with error message. Files ignored by `.gitignore`,
+    // such as "res/gitignore/fixture.txt.orig", are skipped.
+    build_deps::RerunIfChanged::new()
+        .respect_gitignore(true)
+        .paths( "${CARGO_MANIFEST_DIR}/res/*/*" )
+        .unwrap();
 
     // Adding the parent directory "res" to the watch-list will capture new-files being added
-    build_deps::rerun_if_changed_paths( "res/*" ).unwrap();
+    build_deps::rerun_if_changed_paths( "${CARGO_MANIFEST_DIR}/res/*" ).unwrap();
+
+    // Fixtures generated into OUT_DIR, tested by the pattern "${OUT_DIR}/fixtures/*.bin"
+    let fixtures = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("fixtures");
+    std::fs::create_dir_all(&fixtures).unwrap();
+    for (name, size) in &[("small", 16), ("large", 4096)] {
+        std::fs::write(fixtures.join(format!("{}.bin", name)), vec![0xa5u8; *size]).unwrap();
+    }
 }
@@ -0,0 +1,35 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+extern crate test_generator_harness;
+
+use std::path::Path;
+use test_generator_harness::{Harness, Resources};
+
+// Invoked for each resource matching "res/*/input.txt", enumerated when the test binary runs
+fn verify_resource(resource: &Path) {
+    assert!(resource.is_file());
+}
+
+// A function returning an error fails the test, printing the error preceded by the resource
+fn verify_content(resource: &Path) -> std::io::Result<()> {
+    let content = std::fs::read_to_string(resource)?;
+    assert!(!content.is_empty());
+    Ok(())
+}
+
+fn main() {
+    Harness::new()
+        .register(Resources::new("verify_resource", "res/*/input.txt", verify_resource))
+        // "res/names/a-b/x.txt" and "res/names/a_b/x.txt" form the same name, each is suffixed
+        // with a hash of the path, such as `verify_named_resource_res_names_a_b_x_txt_60af7a7a`
+        .register(Resources::new("verify_named_resource", "res/names/*/x.txt", verify_resource))
+        .register(
+            Resources::new("verify_content", "res/*/input.txt", verify_content)
+                .pattern("res/*/name.txt")
+                .ignore("res/set3/*"),
+        )
+        .run()
+        .exit();
+}
@@ -7,26 +7,252 @@ extern crate test_generator;
 
 #[cfg(test)]
 mod tests {
-    use test_generator::test_resources;
+    use test_generator::{test_cases, test_matrix, test_resources, test_sections, test_values};
 
     // For all subfolders matching "res/*/input.txt" do generate a test function
     // For example:
     // In case of the following resources in these two folders "rest1/input.txt"
     // and "res/set2/input.txt" the following test functions
-    // would be created
+    // would be created, passing the absolute path of each resource
     // ```
     // #[test]
     // fn verify_resource_res_set1_input_txt() {
-    //     verify_resource("res/set1/input.txt".into());
+    //     verify_resource("/path/to/example/res/set1/input.txt".into());
     // }
     //
     // #[test]
     // fn verify_resource_res_set2_input_txt() {
-    //     verify_resource("res/set2/input.txt".into());
+    //     verify_resource("/path/to/example/res/set2/input.txt".into());
     // }
     // ```
     #[test_resources("res/*/input.txt")]
     fn verify_resource(resource: &str) { assert!(std::path::Path::new(resource).exists()); }
+
+    // The pattern is resolved relative to the workspace root, sharing the folder "res/" among
+    // all member crates of the workspace
+    #[test_resources("res/*/input.txt", root = "workspace")]
+    fn verify_workspace_resource(resource: &str) {
+        assert!(std::path::Path::new(resource).exists());
+        assert!(!resource.starts_with(env!("CARGO_MANIFEST_DIR")));
+    }
+
+    // Union of multiple patterns, "res/set1/input.txt" is matched twice but yields a single test;
+    // resources matching an exclude pattern are skipped
+    #[test_resources("res/set1/*.txt", "res/*/input.txt", exclude = ["res/set3/**"])]
+    fn verify_selected_resource(resource: &str) {
+        assert!(std::path::Path::new(resource).exists());
+        assert!(!resource.contains("set3"));
+    }
+
+    // Paths differing in special characters only, such as "res/names/a-b/x.txt" and
+    // "res/names/a_b/x.txt", form the same canonical name; these generated tests are
+    // distinguished by a hash-suffix, for example `verify_named_resource_res_names_a_b_x_txt_60af7a7a`
+    #[test_resources("res/names/**/*.txt")]
+    fn verify_named_resource(resource: &str) { assert!(std::path::Path::new(resource).exists()); }
+
+    // The resource is passed according to the parameter type: `&Path` and `PathBuf` receive the
+    // path, whereas `&'static str` and `&'static [u8]` receive the contents, embedded at compile-time
+    #[test_resources("res/*/input.txt")]
+    fn verify_resource_path(resource: &std::path::Path) { assert!(resource.is_file()); }
+
+    #[test_resources("res/*/input.txt")]
+    fn verify_resource_path_buf(resource: std::path::PathBuf) { assert!(resource.is_file()); }
+
+    #[test_resources("res/*/input.txt")]
+    fn verify_resource_str(content: &'static str) { assert!(content.starts_with("interface Set")); }
+
+    #[test_resources("res/*/input.txt")]
+    fn verify_resource_bytes(content: &'static [u8]) { assert!(content.starts_with(b"interface Set")); }
+
+    // The placeholder {case} captures the directory of each input, the partner resource
+    // "res/{case}/expect.txt" is passed as second argument, for example
+    // `verify_paired_resource("/path/to/example/res/set1/input.txt", "/path/to/example/res/set1/expect.txt")`
+    #[test_resources("res/{case}/input.txt", expect = "res/{case}/expect.txt")]
+    fn verify_paired_resource(input: &std::path::Path, expect: &std::path::Path) {
+        assert!(input.is_file() && expect.is_file());
+        assert_eq!(input.parent(), expect.parent());
+    }
+
+    // The returned output is compared with the golden file "res/{case}/name.txt"; running the
+    // tests with TEST_GENERATOR_BLESS=1 rewrites the golden files instead
+    #[test_resources("res/{case}/input.txt", golden = "res/{case}/name.txt")]
+    fn verify_golden_resource(input: &'static str) -> String {
+        let name = input.trim_start_matches("interface ").split(' ').next().unwrap();
+        format!("{}\n", name)
+    }
+
+    // The attributes `#[should_panic]`, `#[ignore]` and `#[cfg(...)]` are copied to each generated test
+    #[test_resources("res/*/input.txt")]
+    #[should_panic(expected = "rejected")]
+    fn verify_rejected_resource(resource: &str) { panic!("rejected {}", resource); }
+
+    // The attribute `attr` replaces `#[test]`; async functions are awaited by the generated test,
+    // for example `#[tokio::test] async fn verify_async_resource_res_set1_input_txt()`
+    #[test_resources("res/*/input.txt", attr = tokio::test)]
+    async fn verify_async_resource(resource: &std::path::Path) {
+        assert!(resource.is_file());
+    }
+
+    // The generated tests are grouped into a module named after the function, for example
+    // `verify_grouped_resource::res_set1_input_txt`, selected by `cargo test verify_grouped_resource::`
+    #[test_resources("res/*/input.txt", module = true)]
+    fn verify_grouped_resource(resource: &str) { assert!(std::path::Path::new(resource).exists()); }
+
+    // Only the tests of resources matching `should_panic` are marked `#[should_panic]`, and only
+    // those matching `ignore` are marked `#[ignore]`
+    #[test_resources("res/*/input.txt", should_panic = "res/set2/*", ignore = ["res/set3/*"])]
+    fn verify_marked_resource(resource: &str) {
+        assert!(!resource.contains("set2"), "invalid resource {}", resource);
+    }
+
+    // A test marked `#[should_panic]` returns `()`, so an error returned by the function panics
+    #[test_resources("res/*/input.txt", should_panic = "res/set2/*")]
+    fn verify_marked_fallible_resource(resource: &str) -> Result<(), String> {
+        match resource.contains("set2") {
+            true => Err(format!("invalid resource {}", resource)),
+            false => Ok(()),
+        }
+    }
+
+    // The sidecar files "res/meta/meta.toml" and "res/meta/<resource>.toml" control the generated
+    // tests, for example `verify_metadata_resource_res_meta_invalid_txt_tag_meta_tag_invalid` is
+    // marked `#[should_panic(expected = "invalid input")]`
+    #[test_resources("res/meta/*.txt")]
+    fn verify_metadata_resource(content: &'static str) {
+        assert_ne!(content, "invalid\n", "invalid input");
+        if content == "slow\n" {
+            std::thread::sleep(std::time::Duration::from_secs(60));
+        }
+    }
+
+    // The timeout of "res/meta/meta.toml" runs the test in a separate thread, the error need not
+    // be `Send`
+    #[test_resources("res/meta/fast.txt")]
+    fn verify_timed_resource(content: &'static str) -> Result<(), Box<dyn std::error::Error>> {
+        if content.is_empty() {
+            return Err("empty resource".into());
+        }
+        Ok(())
+    }
+
+    // The sidecar files are not resources of their own, even if matching the pattern
+    #[test_resources("res/meta/*")]
+    fn verify_any_metadata_resource(content: &'static str) {
+        assert!(!content.contains('='), "sidecar file passed as resource");
+        assert_ne!(content, "invalid\n", "invalid input");
+    }
+
+    // The tests of resources listed in "res/xfail/known_failures.txt" pass if panicking, and fail
+    // with "unexpectedly passed, remove it from the xfail list" otherwise
+    #[test_resources("res/xfail/*.in", xfail = "res/xfail/known_failures.txt")]
+    fn verify_known_failure(content: &'static str) { assert_eq!(content, "valid\n"); }
+
+    // A `Result` is returned by the generated test, so `?` fails the test printing the error,
+    // preceded by the resource
+    #[test_resources("res/*/input.txt")]
+    fn verify_fallible_resource(resource: &std::path::Path) -> std::io::Result<()> {
+        let content = std::fs::read_to_string(resource)?;
+        assert!(content.starts_with("interface Set"));
+        Ok(())
+    }
+
+    // A panic is raised again, its message preceded by the function, the resource and the pattern
+    #[test_resources("res/set1/input.txt")]
+    #[should_panic(expected = "function `verify_panic_context` failed on resource res/set1/input.txt")]
+    fn verify_panic_context(content: &'static str) { assert!(content.is_empty()); }
+
+    // The same for async functions, the panic is caught whenever the future is polled
+    #[test_resources("res/set1/input.txt", attr = tokio::test)]
+    #[should_panic(expected = "function `verify_async_panic_context` failed on resource res/set1/input.txt")]
+    async fn verify_async_panic_context(content: &'static str) { assert!(content.is_empty()); }
+
+    // A single test `verify_aggregated_resource_aggregate` iterates all resources, reporting every
+    // failing resource at the end; the known failure is expected to panic
+    #[test_resources("res/**/*.txt", "res/xfail/*.in", mode = "aggregate",
+                     xfail = "res/xfail/known_failures.txt", exclude = "res/meta/**")]
+    fn verify_aggregated_resource(content: &'static [u8]) { assert!(!content.starts_with(b"broken")); }
+
+    // A forwarded `#[should_panic]` expects each of the resources to panic, instead of the test
+    #[test_resources("res/*/input.txt", mode = "aggregate")]
+    #[should_panic(expected = "rejected")]
+    fn verify_aggregated_failure(resource: &str) { panic!("rejected {}", resource); }
+
+    // The test-names are formed from the template, such as `verify_templated_resource_set1_input`
+    // and `verify_templated_resource_set1_expect`, instead of the whole path
+    #[test_resources("res/{case}/input.txt", "res/{case}/expect.txt", name = "{case}_{stem}")]
+    fn verify_templated_resource(resource: &str) { assert!(std::path::Path::new(resource).exists()); }
+
+    // A test for each value, named after the value: `verify_value_0000000010` and so on
+    #[test_values(10, 100, 1000)]
+    fn verify_value(size: usize) { assert!(size >= 10); }
+
+    // Named values, producing the tests `verify_named_value_empty` and `verify_named_value_blank`
+    #[test_values(empty => "", blank => " ")]
+    fn verify_named_value(text: &str) { assert!(text.trim().is_empty()); }
+
+    // Negative values are named `verify_signed_value_neg_0000000001` and so on; expressions such as
+    // calls or operators are named by position, for example `verify_computed_value_value_2`
+    #[test_values(-1, 0, 1)]
+    fn verify_signed_value(value: i32) { assert!(value.abs() <= 1); }
+
+    #[test_values(i32::MAX, i32::MAX - 1, "abc".len() as i32)]
+    fn verify_computed_value(value: i32) { assert!(value > 0); }
+
+    // The same for values, an error of a function marked `#[should_panic]` panics
+    #[test_values(1, 2)]
+    #[should_panic(expected = "positive")]
+    fn verify_rejected_value(value: u32) -> Result<(), &'static str> {
+        if value > 0 {
+            return Err("positive");
+        }
+        Ok(())
+    }
+
+    // A test for each combination of resource and level, such as
+    // `verify_matrix_res_set1_input_txt_level_0000000001`
+    #[test_matrix(resources = "res/*/input.txt", level = [1, 5, 9])]
+    fn verify_matrix(content: &'static str, level: usize) { assert!(content.len() > level); }
+
+    // Negative values of an axis, such as `verify_signed_matrix_res_set1_input_txt_level_neg_0000000001`
+    #[test_matrix(resources = "res/*/input.txt", level = [-1, 0])]
+    fn verify_signed_matrix(content: &'static str, level: i64) { assert!(content.len() as i64 > level); }
+
+    // A test for each row of the table, named after the first column: `verify_csv_case_zero`
+    // and so on; each parameter receives the field of the column of the same name
+    #[test_cases("cases/parse.csv")]
+    fn verify_csv_case(input: &str, expected: i64) { assert_eq!(input.trim().parse::<i64>().unwrap(), expected); }
+
+    #[test_cases("cases/parse.json", key = "name")]
+    fn verify_json_case(input: String, expected: i64) { assert_eq!(input.parse::<i64>().unwrap(), expected); }
+
+    #[test_cases("cases/parse.toml")]
+    fn verify_toml_case(expected: i64, input: &str) { assert_eq!(input.parse::<i64>().unwrap(), expected); }
+
+    // A test for each section of the file, such as `verify_section_nested`, passing the text of
+    // the section and its name
+    #[test_sections("cases/snippets.txt", separator = "^--- (\\w+)")]
+    fn verify_section(text: &str, name: &str) {
+        let depth = text.chars().fold(0, |depth, c| match c {
+            '(' => depth + 1,
+            ')' => depth - 1,
+            _ => depth,
+        });
+        assert_eq!(depth, 0, "unbalanced section {}", name);
+    }
+
+    // Fixtures generated by the build-script into OUT_DIR; the test-names are formed relative to
+    // the variable, such as `verify_generated_fixture_fixtures_small_bin`
+    #[test_resources("${OUT_DIR}/fixtures/*.bin")]
+    fn verify_generated_fixture(content: &'static [u8]) { assert!(content.iter().all(|b| *b == 0xa5)); }
+
+    // The pattern matches `UPPER.TXT` case-insensitively, whereas the dotfile `.hidden.txt`
+    // requires a literal leading dot
+    #[test_resources("res/options/*.txt", case_sensitive = false, require_literal_leading_dot = true)]
+    fn verify_matched_resource(content: &'static str) { assert_ne!(content, "hidden\n"); }
+
+    // The backup `fixture.txt.orig` is skipped, being ignored by `res/gitignore/.gitignore`
+    #[test_resources("res/gitignore/*", require_literal_leading_dot = true, respect_gitignore = true)]
+    fn verify_tracked_resource(content: &'static str) { assert_eq!(content, "tracked\n"); }
 }
 
 #[cfg(test)]
@@ -43,7 +269,7 @@ mod testsuite {
     struct Context<'t> { file: File, name: &'t str }
 
     // Setup - Initializing the resources
-    fn setup<'t>(filename: &str) -> Context {
+    fn setup(filename: &str) -> Context<'_> {
         // unwrap may panic
         Context { file: File::create(filename).unwrap(), name: filename }
     }
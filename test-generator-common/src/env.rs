@@ -0,0 +1,101 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Environment variables within patterns, such as `${OUT_DIR}/fixtures/*.bin`
+
+use std::path::PathBuf;
+
+/// Pattern whose environment variables have been expanded
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expanded {
+    /// The pattern, the values of the variables being escaped
+    pub pattern: String,
+    /// Value of the variable the pattern begins with, if any, being the directory the names of
+    /// the matching paths are formed relative to
+    pub base_dir: Option<PathBuf>,
+    /// Names of the expanded variables, in order of occurrence
+    pub variables: Vec<String>,
+}
+
+/// Failure expanding the environment variables of a pattern
+#[derive(Clone, Debug, PartialEq)]
+pub enum VariableError {
+    /// A variable is not terminated, expected `${NAME}`
+    Unterminated,
+    /// The variable of this name is not defined
+    Undefined(String),
+}
+
+/// Expand the environment variables `${NAME}` of the pattern; the values are escaped by the
+/// function, as they might contain glob meta-characters, for example by `glob::Pattern::escape`.
+pub fn expand_variables<F>(pattern: &str, escape: F) -> Result<Expanded, VariableError>
+where
+    F: Fn(&str) -> String,
+{
+    let mut expanded = Expanded {
+        pattern: String::new(),
+        base_dir: None,
+        variables: Vec::new(),
+    };
+    let mut rest = pattern;
+    while let Some(start) = rest.find("${") {
+        expanded.pattern.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or(VariableError::Unterminated)?;
+        let name = &rest[start + 2..start + end];
+        let value =
+            std::env::var(name).map_err(|_| VariableError::Undefined(name.to_string()))?;
+        if expanded.pattern.is_empty() && start == 0 {
+            expanded.base_dir = Some(PathBuf::from(&value));
+        }
+        expanded.pattern.push_str(&escape(&value));
+        expanded.variables.push(name.to_string());
+        rest = &rest[start + end + 1..];
+    }
+    expanded.pattern.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bracketed(value: &str) -> String {
+        format!("[{}]", value)
+    }
+
+    #[test]
+    fn leading_variable_is_base_dir() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let expanded = expand_variables("${CARGO_MANIFEST_DIR}/res/*.txt", bracketed).unwrap();
+        assert_eq!(expanded.pattern, format!("[{}]/res/*.txt", dir));
+        assert_eq!(expanded.base_dir, Some(PathBuf::from(dir)));
+        assert_eq!(expanded.variables, vec!["CARGO_MANIFEST_DIR".to_string()]);
+    }
+
+    #[test]
+    fn inner_variable_is_no_base_dir() {
+        let expanded = expand_variables("res/${CARGO_PKG_NAME}/*", bracketed).unwrap();
+        assert_eq!(expanded.pattern, "res/[test-generator-common]/*");
+        assert_eq!(expanded.base_dir, None);
+    }
+
+    #[test]
+    fn pattern_without_variables() {
+        let expanded = expand_variables("res/*/input.txt", bracketed).unwrap();
+        assert_eq!(expanded.pattern, "res/*/input.txt");
+        assert!(expanded.variables.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            expand_variables("${CARGO_MANIFEST_DIR/res", bracketed),
+            Err(VariableError::Unterminated)
+        );
+        assert_eq!(
+            expand_variables("${TEST_GENERATOR_UNDEFINED}/res", bracketed),
+            Err(VariableError::Undefined("TEST_GENERATOR_UNDEFINED".to_string()))
+        );
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Skipping paths ignored by `.gitignore` and `.ignore` files
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Ignore files of a directory, in decreasing order of precedence
+const IGNORE_FILES: &[&str] = &[".ignore", ".gitignore", ".git/info/exclude"];
+
+/// Ignore files of the directories containing the paths, read once per directory
+#[derive(Default)]
+pub struct IgnoreFiles {
+    matchers: HashMap<PathBuf, Vec<Gitignore>>,
+}
+
+impl IgnoreFiles {
+    /// Matchers of the ignore files in the directory, in decreasing order of precedence;
+    /// malformed ignore files are skipped
+    fn matchers(&mut self, dir: &Path) -> &[Gitignore] {
+        self.matchers.entry(dir.to_path_buf()).or_insert_with(|| {
+            IGNORE_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|file| file.is_file())
+                .filter_map(|file| {
+                    let mut builder = GitignoreBuilder::new(dir);
+                    match builder.add(&file) {
+                        Some(_) => None,
+                        None => builder.build().ok(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Return true if the path is ignored by the ignore files of its directory or any parent
+    /// directory up to the root of the git repository; files of deeper directories take precedence,
+    /// so a pattern such as `!keep.orig` re-includes a path ignored by a parent directory.
+    ///
+    /// Relative paths are resolved against the working directory, such as the package directory
+    /// of a build-script, reaching the ignore files of its parent directories as well.
+    pub fn is_ignored(&mut self, path: &Path) -> bool {
+        let path = match std::env::current_dir() {
+            Ok(dir) => dir.join(path),
+            Err(_) => path.to_path_buf(),
+        };
+        let path = path.as_path();
+        let is_dir = path.is_dir();
+        for dir in path.ancestors().skip(1) {
+            for matcher in self.matchers(dir) {
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! # Overview
+//! This crate holds the functionality shared by the crates
+//! [test-generator](https://crates.io/crates/test-generator),
+//! [test-generator-harness](https://crates.io/crates/test-generator-harness) and
+//! [build-deps](https://crates.io/crates/build-deps), so the resources are enumerated and the
+//! tests are named the same way at compile-time, at run-time and by the build-script. It is not intended to be used
+//! directly.
+//!
+//! [Documentation](https://docs.rs/test-generator-common/)
+//!
+//! [Repository](https://github.com/frehberg/test-generator/)
+//!
+//! The module `gitignore` requires the optional feature `gitignore`.
+
+#[cfg(feature = "gitignore")]
+extern crate ignore;
+
+pub mod env;
+#[cfg(feature = "gitignore")]
+pub mod gitignore;
+pub mod naming;
@@ -0,0 +1,164 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Names of the tests, formed from the resource paths and values
+
+use std::collections::HashMap;
+
+/// Keywords, which are no valid identifiers for functions
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Form a canonical name, being a valid identifier: delimiters, special characters and non-ASCII
+/// characters are replaced by `_`, and a name beginning with a digit or being a keyword is
+/// prefixed or suffixed by `_`.
+pub fn canonical_name(s: &str) -> String {
+    let mut name: String = s
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    // identifiers must not begin with a digit, and must not be a single '_' or a keyword
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if name == "_" || KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+/// FNV-1a hash folded to 32 bit, stable across compiler versions and platforms
+pub fn stable_hash(s: &str) -> u32 {
+    let hash = s.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    (hash ^ (hash >> 32)) as u32
+}
+
+/// Two candidates forming the same name, even after suffixing the hashes of their origins
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmbiguousName {
+    /// The ambiguous name
+    pub name: String,
+    /// Position of the later of both candidates
+    pub index: usize,
+    /// Origin of the earlier candidate
+    pub first: String,
+    /// Origin of the later candidate
+    pub second: String,
+}
+
+/// Ensure the names are unique. The canonical names may collide, as different characters are
+/// mapped onto '_' and long names are truncated; each colliding name is suffixed with a short
+/// hash of its origin, for example the resource path. The suffix does not depend on the order of
+/// the candidates, so names are stable between builds and runs.
+///
+/// Each candidate is a pair of the name and the origin it has been formed from; if the suffixed
+/// names still collide, both origins are returned as error.
+pub fn unique_names(candidates: &[(String, String)]) -> Result<Vec<String>, AmbiguousName> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+    for (name, _) in candidates {
+        *occurrences.entry(name).or_insert(0) += 1;
+    }
+
+    let mut origins: HashMap<String, &str> = HashMap::new();
+    let mut names = Vec::with_capacity(candidates.len());
+    for (index, (name, origin)) in candidates.iter().enumerate() {
+        let name = if occurrences[name.as_str()] > 1 {
+            format!("{}_{:08x}", name, stable_hash(origin))
+        } else {
+            name.clone()
+        };
+        if let Some(first) = origins.get(&name) {
+            return Err(AmbiguousName {
+                name,
+                index,
+                first: first.to_string(),
+                second: origin.clone(),
+            });
+        }
+        origins.insert(name.clone(), origin);
+        names.push(name);
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, origin)| (name.to_string(), origin.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn canonical_names() {
+        assert_eq!(
+            canonical_name("verify_res/set1/input.txt"),
+            "verify_res_set1_input_txt"
+        );
+        assert_eq!(canonical_name("1st"), "_1st");
+        assert_eq!(canonical_name("fn"), "fn_");
+        assert_eq!(canonical_name(""), "__");
+        assert_eq!(canonical_name("ä"), "__");
+    }
+
+    #[test]
+    fn stable_hashes() {
+        // the suffixes of the names depend on it, they must not change between releases
+        assert_eq!(stable_hash("res/names/a_b/x.txt"), 0x60af_7a7a);
+        assert_eq!(stable_hash("res/names/a-b/x.txt"), 0x633f_b5a1);
+    }
+
+    #[test]
+    fn distinct_names_are_kept() {
+        let names = unique_names(&candidates(&[("a", "x"), ("b", "y")])).unwrap();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn colliding_names_are_suffixed() {
+        let names =
+            unique_names(&candidates(&[("a_b", "a-b"), ("c", "c"), ("a_b", "a_b")])).unwrap();
+        assert_eq!(names[0], format!("a_b_{:08x}", stable_hash("a-b")));
+        assert_eq!(names[1], "c");
+        assert_eq!(names[2], format!("a_b_{:08x}", stable_hash("a_b")));
+    }
+
+    #[test]
+    fn suffixed_names_colliding_are_ambiguous() {
+        // the suffixed name of the first candidate equals the plain name of the third one
+        let suffixed = format!("a_{:08x}", stable_hash("x"));
+        let pairs = vec![
+            ("a".to_string(), "x".to_string()),
+            ("a".to_string(), "y".to_string()),
+            (suffixed.clone(), "z".to_string()),
+        ];
+        assert_eq!(
+            unique_names(&pairs),
+            Err(AmbiguousName {
+                name: suffixed,
+                index: 2,
+                first: "x".to_string(),
+                second: "z".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn identical_origins_are_ambiguous() {
+        let error = unique_names(&candidates(&[("a", "x"), ("a", "x")])).unwrap_err();
+        assert_eq!(error.index, 1);
+        assert_eq!(error.name, format!("a_{:08x}", stable_hash("x")));
+    }
+}
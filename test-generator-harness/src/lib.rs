@@ -0,0 +1,419 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! # Overview
+//! This crate provides a test harness enumerating the resources at run-time, as alternative to the
+//! macro `test_resources` of crate [test-generator](https://crates.io/crates/test-generator)
+//! expanding the pattern at compile-time. Adding a resource does not require re-compiling the tests.
+//!
+//! [Documentation](https://docs.rs/test-generator-harness/)
+//!
+//! [Repository](https://github.com/frehberg/test-generator/)
+//!
+//! # Getting Started
+//!
+//! First of all you have to add this dependency to your `Cargo.toml`, and declare a test target
+//! without the default harness:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! test-generator-harness = "^0.1"
+//!
+//! [[test]]
+//! name = "resources"
+//! harness = false
+//! ```
+//!
+//! The test target `tests/resources.rs` defines the function `main`, registering a function and
+//! the patterns of resources it shall be invoked with:
+//!
+//! ```ignore
+//! use test_generator_harness::{Harness, Resources};
+//!
+//! fn verify_resource(resource: &std::path::Path) {
+//!     assert!(resource.is_file());
+//! }
+//!
+//! fn main() {
+//!     Harness::new()
+//!         .register(Resources::new("verify_resource", "res/*/input.txt", verify_resource))
+//!         .run()
+//!         .exit();
+//! }
+//! ```
+//!
+//! Each resource is a test of its own, named like the tests generated by `test_resources`, for
+//! example `verify_resource_res_set1_input_txt`; colliding names, such as of `res/a-b/x.txt` and
+//! `res/a_b/x.txt`, are suffixed with a short hash of the path; a suffixed name still colliding
+//! fails the registered resources. The command line of libtest is supported, such as `--list`,
+//! `--exact` and `--ignored`, and the process exits with code 101 if any test failed.
+
+extern crate glob;
+extern crate libtest_mimic;
+extern crate test_generator_common;
+
+use glob::{glob, Pattern};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use test_generator_common::naming::{canonical_name, unique_names};
+
+pub use libtest_mimic::{Arguments, Conclusion, Trial};
+
+/// Outcome of a test function, either `()` or a `Result`, failing the test if an error
+pub trait TestOutcome {
+    /// Return the error message, if failed
+    fn into_failure(self) -> Option<String>;
+}
+
+impl TestOutcome for () {
+    fn into_failure(self) -> Option<String> {
+        None
+    }
+}
+
+impl<E: Debug> TestOutcome for Result<(), E> {
+    fn into_failure(self) -> Option<String> {
+        self.err().map(|err| format!("Error: {:?}", err))
+    }
+}
+
+/// Function invoked with the absolute path of each resource
+type TestFn = Arc<dyn Fn(&Path) -> Option<String> + Send + Sync>;
+
+/// Resources matching the patterns, each being tested by the function
+pub struct Resources {
+    name: String,
+    patterns: Vec<String>,
+    exclude: Vec<String>,
+    ignore: Vec<String>,
+    root: Option<PathBuf>,
+    func: TestFn,
+}
+
+impl Resources {
+    /// Test each resource matching the pattern by the function; the name prefixes the names
+    /// of the tests, usually the name of the function
+    pub fn new<F, R>(name: &str, pattern: &str, func: F) -> Resources
+    where
+        F: Fn(&Path) -> R + Send + Sync + 'static,
+        R: TestOutcome,
+    {
+        Resources {
+            name: name.to_string(),
+            patterns: vec![pattern.to_string()],
+            exclude: Vec::new(),
+            ignore: Vec::new(),
+            root: None,
+            func: Arc::new(move |path| func(path).into_failure()),
+        }
+    }
+
+    /// Add another pattern, resources matching any of the patterns are tested
+    pub fn pattern(mut self, pattern: &str) -> Resources {
+        self.patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Skip the resources matching the pattern; relative patterns are matched against the path
+    /// relative to the root directory
+    pub fn exclude(mut self, pattern: &str) -> Resources {
+        self.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Mark the tests of resources matching the pattern as ignored, being run with `--ignored`
+    pub fn ignore(mut self, pattern: &str) -> Resources {
+        self.ignore.push(pattern.to_string());
+        self
+    }
+
+    /// Directory the relative patterns are resolved against; by default the directory
+    /// `CARGO_MANIFEST_DIR` assigned by cargo running the tests, or the working directory
+    pub fn root<P: Into<PathBuf>>(mut self, dir: P) -> Resources {
+        self.root = Some(dir.into());
+        self
+    }
+
+    /// Root directory, the patterns are resolved against
+    fn root_dir(&self) -> PathBuf {
+        self.root
+            .clone()
+            .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Enumerate the resources, sorted by path; an invalid pattern, or a pattern not matching any
+    /// resource, is reported as error
+    fn enumerate(&self, root_dir: &Path) -> Result<Vec<PathBuf>, String> {
+        let exclude = parse_patterns(&self.exclude)?;
+
+        let mut paths = Vec::new();
+        // the root directory is escaped, as it might contain glob meta-characters
+        let escaped_root = PathBuf::from(Pattern::escape(&root_dir.to_string_lossy()));
+        for pattern in &self.patterns {
+            let resolved = escaped_root.join(pattern);
+            let matched = glob(&resolved.to_string_lossy())
+                .map_err(|err| format!("invalid pattern {:?}: {}", pattern, err))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|err| format!("failed to read {}", err))?;
+            if matched.is_empty() {
+                return Err(format!(
+                    "no resource matching the pattern {:?} in directory {}",
+                    pattern,
+                    root_dir.display()
+                ));
+            }
+            paths.extend(matched);
+        }
+        paths.sort();
+        paths.dedup();
+        paths.retain(|path| !matches_any(&exclude, &self.exclude, root_dir, path));
+        Ok(paths)
+    }
+
+    /// A test for each of the resources; failing to enumerate the resources, or to form unique
+    /// names, is reported by a failing test named after the function
+    fn trials(&self) -> Vec<Trial> {
+        let root_dir = self.root_dir();
+        let enumerated = parse_patterns(&self.ignore)
+            .and_then(|ignore| Ok((self.enumerate(&root_dir)?, ignore)));
+        let (paths, ignore) = match enumerated {
+            Ok(enumerated) => enumerated,
+            Err(msg) => return vec![Trial::test(self.name.clone(), move || Err(msg.into()))],
+        };
+
+        let rel_paths = paths
+            .iter()
+            .map(|path| path.strip_prefix(&root_dir).unwrap_or(path).to_path_buf())
+            .collect::<Vec<_>>();
+        // colliding names are suffixed with a hash of the relative path, like by `test_resources`
+        let candidates = rel_paths
+            .iter()
+            .map(|rel_path| {
+                (
+                    test_name(&self.name, rel_path),
+                    rel_path.display().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let names = match unique_names(&candidates) {
+            Ok(names) => names,
+            Err(err) => {
+                let msg = format!(
+                    "test name `{}` is ambiguous, formed from both {:?} and {:?}",
+                    err.name, err.first, err.second
+                );
+                return vec![Trial::test(self.name.clone(), move || Err(msg.into()))];
+            }
+        };
+
+        paths
+            .into_iter()
+            .zip(rel_paths)
+            .zip(names)
+            .map(|((path, rel_path), name)| {
+                let ignored = matches_any(&ignore, &self.ignore, &root_dir, &path);
+                let func = Arc::clone(&self.func);
+                Trial::test(name, move || match func(&path) {
+                    Some(msg) => Err(format!("resource {}: {}", rel_path.display(), msg).into()),
+                    None => Ok(()),
+                })
+                .with_ignored_flag(ignored)
+            })
+            .collect()
+    }
+}
+
+/// Parse each of the patterns, reporting the first invalid one
+fn parse_patterns(patterns: &[String]) -> Result<Vec<Pattern>, String> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|err| format!("invalid pattern {:?}: {}", pattern, err))
+        })
+        .collect()
+}
+
+/// Return true if the path matches any of the patterns; relative patterns are matched against
+/// the path relative to the root directory
+fn matches_any(patterns: &[Pattern], sources: &[String], root_dir: &Path, path: &Path) -> bool {
+    let rel_path = path.strip_prefix(root_dir).unwrap_or(path);
+    patterns.iter().zip(sources).any(|(pattern, source)| {
+        if Path::new(source).is_absolute() {
+            pattern.matches_path(path)
+        } else {
+            pattern.matches_path(rel_path)
+        }
+    })
+}
+
+/// Name of the test, the prefix and the relative path, replacing all characters not permitted in
+/// identifiers by `_`, like the names of the tests generated by `test_resources`
+fn test_name(prefix: &str, rel_path: &Path) -> String {
+    canonical_name(&format!("{}_{}", prefix, rel_path.display()))
+}
+
+/// Test harness, running the tests of all registered resources
+#[derive(Default)]
+pub struct Harness {
+    resources: Vec<Resources>,
+}
+
+impl Harness {
+    /// Harness without any tests
+    pub fn new() -> Harness {
+        Harness::default()
+    }
+
+    /// Register the resources and the function testing them
+    pub fn register(mut self, resources: Resources) -> Harness {
+        self.resources.push(resources);
+        self
+    }
+
+    /// Return a test for each of the resources
+    pub fn trials(&self) -> Vec<Trial> {
+        self.resources.iter().flat_map(Resources::trials).collect()
+    }
+
+    /// Run the tests according to the command line arguments; the conclusion provides the
+    /// exit code by `Conclusion::exit`
+    pub fn run(self) -> Conclusion {
+        self.run_with_args(&Arguments::from_args())
+    }
+
+    /// Run the tests according to the given arguments
+    pub fn run_with_args(self, args: &Arguments) -> Conclusion {
+        libtest_mimic::run(args, self.trials())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use test_generator_common::naming::stable_hash;
+
+    /// Directory of the test containing the files, created afresh
+    fn resource_dir(test: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "test-generator-harness-{}-{}",
+            std::process::id(),
+            test
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        for file in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "content").unwrap();
+        }
+        dir
+    }
+
+    fn verify_file(resource: &Path) {
+        assert!(resource.is_file());
+    }
+
+    fn names(trials: &[Trial]) -> Vec<&str> {
+        trials.iter().map(Trial::name).collect()
+    }
+
+    fn num_failed(resources: Resources) -> u64 {
+        let args = Arguments {
+            quiet: true,
+            test_threads: Some(1),
+            ..Arguments::default()
+        };
+        Harness::new()
+            .register(resources)
+            .run_with_args(&args)
+            .num_failed
+    }
+
+    #[test]
+    fn resources_are_enumerated() {
+        let dir = resource_dir("enumerate", &["b/input.txt", "a/input.txt", "a/name.txt"]);
+        let resources = Resources::new("verify", "*/input.txt", verify_file)
+            .pattern("a/*.txt")
+            .root(&dir);
+        let trials = resources.trials();
+        // sorted by path, "a/input.txt" matching both patterns is tested once
+        assert_eq!(
+            names(&trials),
+            vec![
+                "verify_a_input_txt",
+                "verify_a_name_txt",
+                "verify_b_input_txt"
+            ]
+        );
+        assert!(trials.iter().all(|trial| !trial.has_ignored_flag()));
+        assert_eq!(num_failed(resources), 0);
+    }
+
+    #[test]
+    fn resources_are_excluded_and_ignored() {
+        let dir = resource_dir("exclude", &["a/input.txt", "b/input.txt", "c/input.txt"]);
+        let trials = Resources::new("verify", "*/input.txt", verify_file)
+            .exclude("b/*")
+            .ignore("c/*")
+            .root(&dir)
+            .trials();
+        assert_eq!(
+            names(&trials),
+            vec!["verify_a_input_txt", "verify_c_input_txt"]
+        );
+        assert!(!trials[0].has_ignored_flag());
+        assert!(trials[1].has_ignored_flag());
+    }
+
+    #[test]
+    fn absolute_exclude_patterns() {
+        let dir = resource_dir("absolute", &["a/input.txt", "b/input.txt"]);
+        let excluded = format!("{}/a/*", Pattern::escape(&dir.to_string_lossy()));
+        let trials = Resources::new("verify", "*/input.txt", verify_file)
+            .exclude(&excluded)
+            .root(&dir)
+            .trials();
+        assert_eq!(names(&trials), vec!["verify_b_input_txt"]);
+    }
+
+    #[test]
+    fn missing_resources_fail() {
+        let dir = resource_dir("missing", &["a/input.txt"]);
+        // reported by a single test named after the function, failing when run
+        let missing = Resources::new("verify", "*/missing.txt", verify_file).root(&dir);
+        assert_eq!(names(&missing.trials()), vec!["verify"]);
+
+        let invalid = Resources::new("verify", "*/input.txt", verify_file)
+            .ignore("[")
+            .root(&dir);
+        assert_eq!(names(&invalid.trials()), vec!["verify"]);
+    }
+
+    #[test]
+    fn colliding_names_are_suffixed() {
+        let dir = resource_dir("collide", &["a-b/x.txt", "a_b/x.txt", "c/x.txt"]);
+        let trials = Resources::new("verify", "*/x.txt", verify_file)
+            .root(&dir)
+            .trials();
+        assert_eq!(
+            names(&trials),
+            vec![
+                format!("verify_a_b_x_txt_{:08x}", stable_hash("a-b/x.txt")).as_str(),
+                format!("verify_a_b_x_txt_{:08x}", stable_hash("a_b/x.txt")).as_str(),
+                "verify_c_x_txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_names_fail() {
+        // the suffixed name of "a-b/x" equals the name formed from the third resource
+        let suffixed = format!("a_b/x_{:08x}", stable_hash("a-b/x"));
+        let dir = resource_dir("ambiguous", &["a-b/x", "a_b/x", &suffixed]);
+        let ambiguous = Resources::new("verify", "*/x*", verify_file).root(&dir);
+        assert_eq!(names(&ambiguous.trials()), vec!["verify"]);
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Aggregate mode, a single test iterating all resources and reporting the failures at the end
+
+use quote::quote;
+
+/// Resource tested by the aggregated test
+pub(crate) struct AggregateEntry {
+    /// Path relative to the root directory, naming the resource in the summary
+    pub(crate) rel_path: String,
+    /// Static value of the resource, see `ResourceParam::value`
+    pub(crate) resource: proc_macro2::TokenStream,
+    /// Static value of the partner resource, if any
+    pub(crate) partner: Option<proc_macro2::TokenStream>,
+    /// Path of the golden file, if any
+    pub(crate) golden: Option<String>,
+    /// Text the panic message must contain if the resource is expected to fail, possibly empty
+    pub(crate) expected_failure: Option<String>,
+}
+
+/// Types of the static values of an entry, the partner and the golden file being optional
+pub(crate) struct AggregateTypes {
+    pub(crate) resource: proc_macro2::TokenStream,
+    pub(crate) partner: Option<proc_macro2::TokenStream>,
+    pub(crate) golden: bool,
+}
+
+/// Statements running the body for each entry, the body referring to the local variables
+/// `resource`, `partner` and `golden`. Each panic is caught; at the end, the test fails with a
+/// summary listing every failing resource and its message.
+pub(crate) fn aggregate_loop(
+    entries: &[AggregateEntry],
+    types: AggregateTypes,
+    body: proc_macro2::TokenStream,
+    ignored: usize,
+) -> proc_macro2::TokenStream {
+    let unit = quote! { () };
+    let rows = entries.iter().map(|entry| {
+        let rel_path = &entry.rel_path;
+        let resource = &entry.resource;
+        let partner = entry.partner.as_ref().unwrap_or(&unit);
+        let golden = match entry.golden {
+            Some(ref golden) => quote! { #golden },
+            None => unit.clone(),
+        };
+        let should_fail = entry.expected_failure.is_some();
+        let expected = entry.expected_failure.as_deref().unwrap_or("");
+        quote! { (#rel_path, #resource, #partner, #golden, #should_fail, #expected) }
+    });
+
+    // the element type is spelled out, coercing embedded contents of any length to a slice
+    let resource_type = types.resource;
+    let (partner_type, partner) = match types.partner {
+        Some(partner_type) => (partner_type, quote! { partner }),
+        None => (unit.clone(), quote! { _ }),
+    };
+    let (golden_type, golden) = match types.golden {
+        true => (quote! { &'static str }, quote! { golden }),
+        false => (unit.clone(), quote! { _ }),
+    };
+
+    quote! {
+        let resources: &[(&str, #resource_type, #partner_type, #golden_type, bool, &str)] = &[
+            #( #rows ),*
+        ];
+        let mut failures: Vec<String> = Vec::new();
+        for &(path, resource, #partner, #golden, should_fail, expected) in resources {
+            let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { #body }));
+            let message = match outcome {
+                Ok(()) => None,
+                Err(panic) => Some(match panic.downcast_ref::<&str>() {
+                    Some(message) => message.to_string(),
+                    None => panic
+                        .downcast_ref::<String>()
+                        .cloned()
+                        .unwrap_or_else(|| "panic without message".to_string()),
+                }),
+            };
+            match (message, should_fail) {
+                (None, false) => {}
+                (Some(message), false) => failures.push(format!("{}: {}", path, message)),
+                (None, true) => failures.push(format!("{}: unexpectedly passed", path)),
+                (Some(ref message), true) if message.contains(expected) => {}
+                (Some(message), true) => failures.push(format!(
+                    "{}: panic message does not contain {:?}: {}",
+                    path, expected, message
+                )),
+            }
+        }
+        if !failures.is_empty() {
+            panic!(
+                "{} of {} resources failed ({} ignored):\n{}",
+                failures.len(),
+                resources.len(),
+                #ignored,
+                failures.join("\n")
+            );
+        }
+    }
+}
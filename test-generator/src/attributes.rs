@@ -0,0 +1,242 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Parameters of the attributes `test_resources` and `bench_resources`
+
+use glob::MatchOptions;
+use proc_macro2::{Span, TokenTree};
+use quote::quote;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{
+    bracketed, token, Attribute, Error, Ident, ItemFn, Lit, LitBool, LitStr, Path, Token,
+};
+
+use crate::resources::ResourceRoot;
+
+/// Return the string value of the literal, or an error pointing at the literal
+pub(crate) fn lit_str_value(lit: &Lit) -> Result<String> {
+    let found = match lit {
+        Lit::Str(l) => return Ok(l.value()),
+        Lit::Bool(l) => format!("'{}'", l.value),
+        Lit::Byte(l) => format!("'{}'", l.value()),
+        Lit::ByteStr(_) => "byte-string".to_string(),
+        Lit::Char(l) => format!("'{}'", l.value()),
+        Lit::Int(l) => format!("'{}'", l.value()),
+        Lit::Float(l) => format!("'{}'", l.value()),
+        _ => "literal".to_string(),
+    };
+    Err(Error::new_spanned(
+        lit,
+        format!(
+            "expected string literal such as \"res/*/input.txt\", got {}",
+            found
+        ),
+    ))
+}
+
+/// Parse either a single string literal or a bracketed list of string literals,
+/// for example `"res/broken/**"` or `["res/broken/**", "res/wip/**"]`
+fn parse_lit_str_list(input: ParseStream) -> Result<Vec<LitStr>> {
+    if input.peek(LitStr) {
+        return Ok(vec![input.parse()?]);
+    }
+    let content;
+    bracketed!(content in input);
+    let list = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+    Ok(list.into_iter().collect())
+}
+
+/// Parse the attribute replacing `#[test]`, a path optionally followed by arguments in
+/// parentheses, for example `tokio::test` or `tokio::test(flavor = "multi_thread")`
+fn parse_test_attr(input: ParseStream) -> Result<proc_macro2::TokenStream> {
+    let path = input.call(Path::parse_mod_style)?;
+    let mut tokens = quote! { #path };
+    if input.peek(token::Paren) {
+        tokens.extend(Some(input.parse::<TokenTree>()?));
+    }
+    Ok(tokens)
+}
+
+/// Attributes of the annotated function, copied to each of the generated functions
+const FORWARDED_ATTRS: &[&str] = &["should_panic", "ignore", "cfg"];
+
+/// Remove the attributes to be forwarded to the generated functions from the annotated
+/// function and return them; `cfg` attributes are kept, as the annotated function must be
+/// configured in or out together with the generated functions.
+pub(crate) fn take_forwarded_attrs(func: &mut ItemFn) -> Vec<Attribute> {
+    let (forwarded, kept): (Vec<Attribute>, Vec<Attribute>) = func
+        .attrs
+        .drain(..)
+        .partition(|attr| FORWARDED_ATTRS.iter().any(|name| attr.path.is_ident(name)));
+    func.attrs = kept;
+    func.attrs
+        .extend(forwarded.iter().filter(|attr| attr.path.is_ident("cfg")).cloned());
+    forwarded
+}
+
+/// MacroAttributes elements
+pub(crate) struct MacroAttributes {
+    /// Include patterns, at least one
+    pub(crate) patterns: Vec<Lit>,
+    /// Base directory, the patterns are resolved against
+    pub(crate) root: Option<LitStr>,
+    /// Exclude patterns, removing resources matched by the include patterns
+    pub(crate) exclude: Vec<LitStr>,
+    /// Template of the partner resource, such as `res/{case}/expect.txt`
+    pub(crate) expect: Option<LitStr>,
+    /// Template of the golden file, the output of the function is compared with
+    pub(crate) golden: Option<LitStr>,
+    /// Attribute replacing `#[test]` or `#[bench]`, such as `tokio::test`
+    pub(crate) attr: Option<proc_macro2::TokenStream>,
+    /// Group the generated functions into a module named after the annotated function
+    pub(crate) module: bool,
+    /// Patterns of resources, whose generated tests are marked `#[ignore]`
+    pub(crate) ignore: Vec<LitStr>,
+    /// Patterns of resources, whose generated tests are marked `#[should_panic]`
+    pub(crate) should_panic: Vec<LitStr>,
+    /// List of known failures, resources whose tests are expected to panic
+    pub(crate) xfail: Option<LitStr>,
+    /// Mode of generation, a test for each resource (default), or a single aggregated test
+    pub(crate) mode: Option<LitStr>,
+    /// Template of the test-name, such as `{parent}_{stem}`, instead of the relative path
+    pub(crate) name: Option<LitStr>,
+    /// Options matching the patterns, such as `case_sensitive = false`
+    pub(crate) match_options: MatchOptions,
+    /// Skip resources ignored by `.gitignore` and `.ignore` files
+    pub(crate) respect_gitignore: bool,
+}
+
+impl MacroAttributes {
+    /// Absolute base directory, the patterns are resolved against
+    pub(crate) fn root_dir(&self) -> Result<PathBuf> {
+        match self.root {
+            Some(ref name) => ResourceRoot::from_name(&name.value())
+                .ok_or_else(|| {
+                    Error::new(
+                        name.span(),
+                        format!(
+                            "unknown root '{}', expected \"manifest\", \"workspace\" or \"out_dir\"",
+                            name.value()
+                        ),
+                    )
+                })?
+                .dir(name.span()),
+            None => ResourceRoot::Manifest.dir(Span::call_site()),
+        }
+    }
+
+    /// The `mode` literal, if the resources are aggregated into a single test
+    pub(crate) fn aggregate(&self) -> Result<Option<&LitStr>> {
+        match self.mode {
+            Some(ref mode) => match mode.value().as_str() {
+                "aggregate" => Ok(Some(mode)),
+                "each" => Ok(None),
+                other => Err(Error::new(
+                    mode.span(),
+                    format!("unknown mode '{}', expected \"each\" or \"aggregate\"", other),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+/// MacroAttributes parser, one or more patterns followed by optional `name = value` parameters
+impl Parse for MacroAttributes {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut patterns = vec![input.parse::<Lit>()?];
+        let mut root = None;
+        let mut exclude = Vec::new();
+        let mut expect = None;
+        let mut golden = None;
+        let mut attr = None;
+        let mut module = false;
+        let mut ignore = Vec::new();
+        let mut should_panic = Vec::new();
+        let mut xfail = None;
+        let mut mode = None;
+        let mut name_template = None;
+        let mut match_options = MatchOptions::new();
+        let mut respect_gitignore = false;
+        let mut named_params = false;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            if input.peek(Lit) {
+                if named_params {
+                    return Err(input.error("patterns must precede the named parameters"));
+                }
+                patterns.push(input.parse()?);
+                continue;
+            }
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            named_params = true;
+            match name.to_string().as_str() {
+                "root" => root = Some(input.parse::<LitStr>()?),
+                "exclude" => exclude.extend(parse_lit_str_list(input)?),
+                "expect" => expect = Some(input.parse::<LitStr>()?),
+                "golden" => golden = Some(input.parse::<LitStr>()?),
+                "attr" => attr = Some(parse_test_attr(input)?),
+                "module" => module = input.parse::<LitBool>()?.value,
+                "ignore" => ignore.extend(parse_lit_str_list(input)?),
+                "should_panic" => should_panic.extend(parse_lit_str_list(input)?),
+                "xfail" => xfail = Some(input.parse::<LitStr>()?),
+                "mode" => mode = Some(input.parse::<LitStr>()?),
+                "name" => name_template = Some(input.parse::<LitStr>()?),
+                "case_sensitive" => match_options.case_sensitive = input.parse::<LitBool>()?.value,
+                "require_literal_separator" => {
+                    match_options.require_literal_separator = input.parse::<LitBool>()?.value
+                }
+                "require_literal_leading_dot" => {
+                    match_options.require_literal_leading_dot = input.parse::<LitBool>()?.value
+                }
+                "respect_gitignore" => {
+                    let value = input.parse::<LitBool>()?;
+                    if value.value && cfg!(not(feature = "gitignore")) {
+                        return Err(Error::new(
+                            value.span,
+                            "respect_gitignore requires the feature `gitignore` of test-generator",
+                        ));
+                    }
+                    respect_gitignore = value.value
+                }
+                other => {
+                    return Err(Error::new(
+                        name.span(),
+                        format!(
+                            "unknown parameter '{}', expected 'root', 'exclude', 'expect', \
+                             'golden', 'attr', 'module', 'ignore', 'should_panic', 'xfail', 'mode', \
+                             'name', 'case_sensitive', 'require_literal_separator', \
+                             'require_literal_leading_dot' or 'respect_gitignore'",
+                            other
+                        ),
+                    ))
+                }
+            }
+        }
+
+        Ok(MacroAttributes {
+            patterns,
+            root,
+            exclude,
+            expect,
+            golden,
+            attr,
+            module,
+            ignore,
+            should_panic,
+            xfail,
+            mode,
+            name: name_template,
+            match_options,
+            respect_gitignore,
+        })
+    }
+}
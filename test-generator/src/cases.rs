@@ -0,0 +1,101 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Generating a test- or bench-function for each case of arguments, such as the values of
+//! `test_values`
+
+use proc_macro2::Ident;
+use quote::quote;
+use syn::parse::Result;
+use syn::{Error, ItemFn};
+
+use crate::attributes::take_forwarded_attrs;
+use crate::context::{error_context, panic_context, unwrap_error};
+use crate::naming::unique_idents;
+use crate::params::returns_result;
+use crate::GenKind;
+
+/// Arguments the annotated function is invoked with by one of the generated functions
+pub(crate) struct Case {
+    /// Identifier of the generated function, suffixed by a hash if colliding with another case
+    pub(crate) ident: Ident,
+    /// Origin the identifier has been formed from, such as the expression
+    pub(crate) origin: String,
+    /// Arguments, following the bencher in case of benches
+    pub(crate) args: proc_macro2::TokenStream,
+    /// Description of the case, preceding the panic message if the function fails
+    pub(crate) context: String,
+}
+
+/// Generate a test- or bench-function for each case, invoking the annotated function with the
+/// arguments of the case; the cases are formed from the parsed function, such as its name.
+pub(crate) fn expand_cases<F>(
+    kind: GenKind,
+    func: proc_macro::TokenStream,
+    cases: F,
+) -> Result<proc_macro2::TokenStream>
+where
+    F: FnOnce(&ItemFn) -> Result<Vec<Case>>,
+{
+    let mut func_ast: ItemFn = syn::parse(func)?;
+    let forwarded = &take_forwarded_attrs(&mut func_ast);
+    if let Some(asyncness) = func_ast.asyncness {
+        return Err(Error::new_spanned(
+            asyncness,
+            "async functions are not supported, use test_resources with `attr` instead",
+        ));
+    }
+
+    let cases = cases(&func_ast)?;
+    let idents = unique_idents(
+        cases
+            .iter()
+            .map(|case| (case.ident.clone(), case.origin.clone()))
+            .collect(),
+    )?;
+
+    let func_ident = &func_ast.ident;
+    // a test marked `should_panic` must return `()`, an error panics instead
+    let panics = forwarded.iter().any(|attr| attr.path.is_ident("should_panic"));
+    let fallible = returns_result(&func_ast);
+    let func_output = &func_ast.decl.output;
+    let items = cases.iter().zip(idents).map(|(case, test_ident)| {
+        let args = &case.args;
+        match kind {
+            GenKind::Test => {
+                let call = quote! { #func_ident ( #args ) };
+                let (output, body) = if fallible && !panics {
+                    let body = panic_context(call, &case.context);
+                    (Some(func_output), error_context(body, &case.context))
+                } else if fallible {
+                    let call = unwrap_error(call);
+                    (None, panic_context(quote! { #call; }, &case.context))
+                } else {
+                    (None, panic_context(quote! { #call; }, &case.context))
+                };
+                quote! {
+                    #[test]
+                    #( #forwarded )*
+                    #[allow(non_snake_case)]
+                    fn #test_ident () #output {
+                        #body
+                    }
+                }
+            }
+            GenKind::Bench => quote! {
+                #[bench]
+                #( #forwarded )*
+                #[allow(non_snake_case)]
+                fn #test_ident (b: &mut test::Bencher) {
+                    #func_ident ( b, #args );
+                }
+            },
+        }
+    });
+
+    Ok(quote! {
+        #func_ast
+        #( #items )*
+    })
+}
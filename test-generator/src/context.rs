@@ -0,0 +1,91 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Reporting the resource a generated test failed on
+
+use quote::quote;
+
+/// Statements running the body; a panic is caught and raised again, its message preceded by
+/// the context naming the resource. Panics without message are propagated unchanged. The value
+/// of the body is returned.
+pub(crate) fn panic_context(
+    body: proc_macro2::TokenStream,
+    context: &str,
+) -> proc_macro2::TokenStream {
+    let raise = raise_with_context(context);
+    quote! {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { #body })) {
+            Ok(result) => result,
+            Err(panic) => #raise,
+        }
+    }
+}
+
+/// Statements awaiting the body, the same as `panic_context` for async functions; each poll of
+/// the future is guarded, as the body is not run by a single call.
+pub(crate) fn async_panic_context(
+    body: proc_macro2::TokenStream,
+    context: &str,
+) -> proc_macro2::TokenStream {
+    let raise = raise_with_context(context);
+    quote! {
+        let mut future = Box::pin(async { #body });
+        let outcome = std::future::poll_fn(|cx| {
+            let poll = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                std::future::Future::poll(future.as_mut(), cx)
+            }));
+            match poll {
+                Ok(std::task::Poll::Ready(result)) => std::task::Poll::Ready(Ok(result)),
+                Ok(std::task::Poll::Pending) => std::task::Poll::Pending,
+                Err(panic) => std::task::Poll::Ready(Err(panic)),
+            }
+        })
+        .await;
+        match outcome {
+            Ok(result) => result,
+            Err(panic) => #raise,
+        }
+    }
+}
+
+/// Expression raising the caught `panic` again, its message preceded by the context
+fn raise_with_context(context: &str) -> proc_macro2::TokenStream {
+    quote! {
+        {
+            let message = match panic.downcast_ref::<&str>() {
+                Some(message) => Some(message.to_string()),
+                None => panic.downcast_ref::<String>().cloned(),
+            };
+            match message {
+                Some(message) => panic!("{}:\n{}", #context, message),
+                None => std::panic::resume_unwind(panic),
+            }
+        }
+    }
+}
+
+/// Statements returning the `Result` of the body; an error is preceded by the context naming
+/// the resource, the error itself is printed by the test harness.
+pub(crate) fn error_context(
+    body: proc_macro2::TokenStream,
+    context: &str,
+) -> proc_macro2::TokenStream {
+    quote! {
+        let result = { #body };
+        if result.is_err() {
+            eprintln!("{}", #context);
+        }
+        result
+    }
+}
+
+/// Expression unwrapping the `Result` returned by the call, panicking with the error
+pub(crate) fn unwrap_error(call: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        match #call {
+            Ok(output) => output,
+            Err(err) => panic!("{:?}", err),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Environment variables within patterns, such as `${OUT_DIR}/fixtures/*.bin`
+
+use glob::Pattern;
+use quote::ToTokens;
+use std::path::PathBuf;
+use syn::parse::Result;
+use syn::Error;
+use test_generator_common::env::{self, VariableError};
+
+/// Expand the environment variables `${NAME}` of the pattern, as defined for the compiler
+/// expanding the macro; undefined variables are reported at the given tokens. The values are
+/// escaped, as they might contain glob meta-characters or braces.
+///
+/// If the pattern begins with a variable, its value is returned as well, being the directory
+/// the names of the resources are formed relative to.
+pub(crate) fn expand_variables<T: ToTokens>(
+    pattern: &str,
+    spanned: &T,
+) -> Result<(String, Option<PathBuf>)> {
+    let escape = |value: &str| Pattern::escape(value).replace('{', "{{").replace('}', "}}");
+    match env::expand_variables(pattern, escape) {
+        Ok(expanded) => Ok((expanded.pattern, expanded.base_dir)),
+        Err(VariableError::Unterminated) => Err(Error::new_spanned(
+            spanned,
+            "unterminated variable, expected `${NAME}`",
+        )),
+        Err(VariableError::Undefined(name)) => {
+            let hint = match name.as_str() {
+                "OUT_DIR" => ", it requires a build-script `build.rs`",
+                _ => "",
+            };
+            Err(Error::new_spanned(
+                spanned,
+                format!(
+                    "environment variable `{}` of the pattern is not defined{}",
+                    name, hint
+                ),
+            ))
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Comparing the output of a test with its golden file, the expected output
+
+use quote::quote;
+
+/// Environment variable, if set to `1` the golden files are rewritten instead of compared
+const BLESS_VAR: &str = "TEST_GENERATOR_BLESS";
+
+/// Statements comparing the value returned by `call` with the contents of the golden file, the
+/// path being an expression of type `&str`. The value may be of any type implementing
+/// `AsRef<[u8]>`, such as `String` or `Vec<u8>`. The golden file is read at run-time, so it may
+/// be created or updated by blessing.
+pub(crate) fn golden_assertion(
+    call: proc_macro2::TokenStream,
+    golden: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let hint = format!("run with {}=1 to bless the output", BLESS_VAR);
+    quote! {
+        let actual = #call;
+        let actual: &[u8] = std::convert::AsRef::<[u8]>::as_ref(&actual);
+        let golden = std::path::Path::new(#golden);
+        if std::env::var(#BLESS_VAR).as_deref() == Ok("1") {
+            if let Err(err) = std::fs::write(golden, actual) {
+                panic!("failed to write golden file {}: {}", golden.display(), err);
+            }
+        } else {
+            let expected = match std::fs::read(golden) {
+                Ok(expected) => expected,
+                Err(err) => panic!(
+                    "failed to read golden file {}: {}; {}",
+                    golden.display(), err, #hint
+                ),
+            };
+            if actual != expected.as_slice() {
+                panic!(
+                    "output differs from golden file {}; {}\n--- expected\n{}\n--- actual\n{}",
+                    golden.display(),
+                    #hint,
+                    String::from_utf8_lossy(&expected),
+                    String::from_utf8_lossy(actual)
+                );
+            }
+        }
+    }
+}
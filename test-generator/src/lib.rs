@@ -2,6 +2,10 @@
 //! This crate provides `#[test_resources]` and `#[bench_resources]` procedural macro attributes
 //! that generates multiple parametrized tests using one body with different resource input parameters.
 //! A test is generated for each resource matching the specific resource location pattern.
+//! The attributes `#[test_values]` and `#[bench_values]` generate a test for each value instead,
+//! `#[test_matrix]` a test for each combination of resources and values, `#[test_cases]` a
+//! test for each row of a CSV, JSON or TOML table, and `#[test_sections]` a test for each
+//! section of a file.
 //!
 //! [![Crates.io](https://img.shields.io/crates/v/test-generator.svg)](https://crates.io/crates/test-generator)
 //! [![MIT License](http://img.shields.io/badge/license-MIT-blue.svg)](https://github.com/frehberg/test-generator/blob/master/LICENSE-MIT)
@@ -20,8 +24,9 @@
 //! [dev-dependencies]
 //! test-generator = "^0.3"
 //! ```
-//! The test-functionality is supports stable Rust since version 1.30,
-//! whereas the bench-functionality requires an API from unstable nightly release.
+//! The test-functionality is supports stable Rust since version 1.71, the optional feature
+//! `gitignore` since version 1.88, whereas the bench-functionality requires an API from unstable
+//! nightly release.
 //!
 //! ```ignore
 //! #![cfg(test)]
@@ -35,7 +40,7 @@
 //!
 //! # Example usage `test`:
 //!
-//! The `test` functionality supports the stable release of Rust-compiler since version 1.30.
+//! The `test` functionality supports the stable release of Rust-compiler since version 1.71.
 //!
 //! ```ignore
 //! #![cfg(test)]
@@ -97,6 +102,12 @@
 //! and configuration of these macros, in combination with the crate
 //! `build-deps` monitoring for any change of these resource files and conditional rebuild.
 //!
+//! # Conditional Build
+//! Each resource matching the pattern is registered as compile-time dependency, so modifying a
+//! resource expands the macro again, without any build-script. Resources being added are detected
+//! by a build-script `build.rs` using the crate `build-deps`, or by enabling the feature `nightly`
+//! on nightly Rust, registering the directories containing the resources as well.
+//!
 //! # Internals
 //! Let's assume the following code and 3 files matching the pattern "res/*/input.txt"
 //! ```ignore
@@ -104,65 +115,521 @@
 //! fn verify_resource(resource: &str) { assert!(std::path::Path::new(resource).exists()); }
 //! ```
 //! the generated code for this input resource will look like
-//! ```
+//! ```ignore
 //! #[test]
 //! #[allow(non_snake_case)]
-//! fn verify_resource_res_set1_input_txt() { verify_resource("res/set1/input.txt".into()); }
+//! fn verify_resource_res_set1_input_txt() { verify_resource("/path/to/crate/res/set1/input.txt".into()); }
 //! #[test]
 //! #[allow(non_snake_case)]
-//! fn verify_resource_res_set2_input_txt() { verify_resource("res/set2/input.txt".into()); }
+//! fn verify_resource_res_set2_input_txt() { verify_resource("/path/to/crate/res/set2/input.txt".into()); }
 //! #[test]
 //! #[allow(non_snake_case)]
-//! fn verify_resource_res_set3_input_txt() { verify_resource("res/set3/input.txt".into()); }
+//! fn verify_resource_res_set3_input_txt() { verify_resource("/path/to/crate/res/set3/input.txt".into()); }
 //! ```
+//! Note: The pattern is resolved relative to the directory of the crate's `Cargo.toml`, and the
+//! generated functions are invoked with the absolute path of each resource.
+//!
 //! Note: The trailing `into()` method-call permits users to implement the `Into`-Trait for auto-conversations.
+//! Parameters of type `&Path` or `PathBuf` receive the path, parameters of type `&'static str` or
+//! `&'static [u8]` receive the contents of the resource, embedded by `include_str!` or `include_bytes!`.
 //!
+#![cfg_attr(feature = "nightly", feature(proc_macro_tracked_path))]
+
 extern crate glob;
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
 
-use self::glob::{glob, Paths};
+use glob::MatchOptions;
+use proc_macro2::Span;
 use quote::quote;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{parse_macro_input, Expr, ExprLit, Ident, Lit, Token, ItemFn};
-
-// Form canonical name without any punctuation/delimiter or special character
-fn canonical_fn_name(s: &str) -> String {
-    // remove delimiters and special characters
-    s.replace(
-        &['"', ' ', '.', ':', '-', '*', '/', '\\', '\n', '\t', '\r'][..],
-        "_",
-    )
+use syn::{parse_macro_input, Error, Expr, Ident, Lit, Token, ItemFn};
+
+mod aggregate;
+mod attributes;
+mod cases;
+mod context;
+mod env;
+mod golden;
+mod matrix;
+mod metadata;
+mod naming;
+mod params;
+mod resources;
+mod sections;
+mod table;
+mod template;
+mod values;
+mod xfail;
+
+use crate::aggregate::{aggregate_loop, AggregateEntry, AggregateTypes};
+use crate::attributes::{lit_str_value, take_forwarded_attrs, MacroAttributes};
+use crate::cases::expand_cases;
+use crate::context::{async_panic_context, error_context, panic_context, unwrap_error};
+use crate::golden::golden_assertion;
+use crate::matrix::Matrix;
+use crate::metadata::{timeout_guard, Mark, Metadata};
+use crate::naming::{canonical_fn_name, fn_ident_from_expr, fn_ident_from_path, unique_idents};
+use crate::params::{returns_result, ResourceParam};
+use crate::resources::{
+    collect_resources, existing_partner_path, glob_paths, partner_path, path_to_str,
+    track_resources, Resource, ResourceFilter,
+};
+use crate::sections::SectionAttributes;
+use crate::table::TableAttributes;
+use crate::template::{slash_path, substitute};
+use crate::values::ValueList;
+use crate::xfail::{xfail_guard, XfailList};
+
+/// Group the generated functions into a module named after the annotated function
+fn group_into_module(
+    func_ident: &proc_macro2::Ident,
+    items: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        mod #func_ident {
+            #[allow(unused_imports)]
+            use super::*;
+            #items
+        }
+    }
 }
 
-/// Return the concatenation of two token-streams
-fn concat_ts_cnt(
-    accu: (u64, proc_macro2::TokenStream),
-    other: proc_macro2::TokenStream,
-) -> (u64, proc_macro2::TokenStream) {
-    let (accu_cnt, accu_ts) = accu;
-    (accu_cnt + 1, quote! { #accu_ts #other })
+/// Kind of the functions being generated for each resource
+#[derive(Clone, Copy)]
+pub(crate) enum GenKind {
+    Test,
+    Bench,
 }
 
-/// MacroAttributes elements
-struct MacroAttributes {
-    glob_pattern: Lit,
-}
+/// Expand the pattern and generate a test- or bench-function for each resource,
+/// shared implementation of `test_resources` and `bench_resources`.
+fn expand_resources(
+    kind: GenKind,
+    attrs: MacroAttributes,
+    func: TokenStream,
+) -> Result<proc_macro2::TokenStream> {
+    let root_dir = attrs.root_dir()?;
+
+    let mut func_ast: ItemFn = syn::parse(func)?;
+
+    // attributes such as `#[should_panic]` apply to each of the generated functions
+    let forwarded = &take_forwarded_attrs(&mut func_ast);
+    let func_copy = quote! { #func_ast };
+
+    // the resource is the only parameter of a test, and follows the bencher of a bench
+    let param = match kind {
+        GenKind::Test => ResourceParam::from_fn(&func_ast, 0),
+        GenKind::Bench => ResourceParam::from_fn(&func_ast, 1),
+    };
+    // the partner resource, if any, follows the resource
+    let partner_param = match kind {
+        GenKind::Test => ResourceParam::from_fn(&func_ast, 1),
+        GenKind::Bench => ResourceParam::from_fn(&func_ast, 2),
+    };
 
-/// MacroAttributes parser
-impl Parse for MacroAttributes {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let glob_pattern: Lit = input.parse()?;
-        if ! input.is_empty() {
-            panic!("found multiple parameters, expected one");
+    let func_ident = func_ast.ident.clone();
+
+    // async functions are awaited, requiring an async test attribute such as `tokio::test`;
+    // benches are not, as the future would be dropped without being measured
+    let asyncness = func_ast.asyncness;
+    if let (Some(asyncness), GenKind::Bench) = (asyncness, &kind) {
+        return Err(Error::new(
+            asyncness.span,
+            "async functions are not supported by bench_resources",
+        ));
+    }
+    if let (Some(asyncness), None) = (asyncness, &attrs.attr) {
+        return Err(Error::new(
+            asyncness.span,
+            "async functions require an async test attribute, such as `attr = tokio::test`",
+        ));
+    }
+    let test_attr = match (&attrs.attr, &kind) {
+        (Some(attr), _) => attr.clone(),
+        (None, GenKind::Test) => quote! { test },
+        (None, GenKind::Bench) => quote! { bench },
+    };
+
+    if let (GenKind::Bench, Some(golden)) = (&kind, &attrs.golden) {
+        return Err(Error::new(
+            golden.span(),
+            "golden files are not supported by bench_resources",
+        ));
+    }
+    if let (GenKind::Bench, Some(xfail)) = (&kind, &attrs.xfail) {
+        return Err(Error::new(
+            xfail.span(),
+            "xfail lists are not supported by bench_resources",
+        ));
+    }
+
+    let resources = collect_resources(
+        &root_dir,
+        &attrs.patterns,
+        &attrs.exclude,
+        attrs.match_options,
+        attrs.respect_gitignore,
+    )?;
+
+    let xfail = match attrs.xfail {
+        Some(ref lit) => {
+            let list = XfailList::read(&root_dir, lit)?;
+            list.check_stale(&resources, lit)?;
+            Some(list)
         }
+        None => None,
+    };
 
-        Ok(MacroAttributes {
-            glob_pattern,
-        })
+    let partners = match attrs.expect {
+        Some(ref expect) => resources
+            .iter()
+            .map(|resource| existing_partner_path(&root_dir, resource, expect).map(Some))
+            .collect::<Result<Vec<_>>>()?,
+        None => resources.iter().map(|_| None).collect(),
+    };
+
+    // golden files may not exist yet, being created by blessing the output
+    let goldens = match attrs.golden {
+        Some(ref golden) => resources
+            .iter()
+            .map(|resource| partner_path(&root_dir, resource, golden).map(Some))
+            .collect::<Result<Vec<_>>>()?,
+        None => resources.iter().map(|_| None).collect(),
+    };
+
+    let metadata = resources
+        .iter()
+        .map(|resource| Metadata::read(&resource.path, &attrs.patterns[resource.pattern]))
+        .collect::<Result<Vec<_>>>()?;
+
+    // attributes added to the generated functions of matching resources only, unless the
+    // annotated function carries the attribute already, marking all generated functions;
+    // the metadata of a resource takes precedence over the patterns
+    let ignore = ResourceFilter::parse_all(&attrs.ignore, attrs.match_options)?;
+    let should_panic = ResourceFilter::parse_all(&attrs.should_panic, attrs.match_options)?;
+    let is_forwarded = |name: &str| forwarded.iter().any(|attr| attr.path.is_ident(name));
+    let (ignore_all, should_panic_all) = (is_forwarded("ignore"), is_forwarded("should_panic"));
+    let marks_of = |resource: &Resource, metadata: &Metadata| {
+        let matches = |filters: &[ResourceFilter]| {
+            filters
+                .iter()
+                .any(|filter| filter.matches(resource))
+                .then_some(Mark::Flag(true))
+        };
+        let ignore_mark = match ignore_all {
+            true => None,
+            false => metadata.ignore.clone().or_else(|| matches(&ignore)),
+        };
+        let should_panic_mark = match should_panic_all {
+            true => None,
+            false => metadata.should_panic.clone().or_else(|| matches(&should_panic)),
+        };
+        (ignore_mark, should_panic_mark)
+    };
+
+    let fallible = returns_result(&func_ast);
+    let func_output = func_ast.decl.output.clone();
+
+    let func_path = if attrs.module {
+        quote! { super:: #func_ident }
+    } else {
+        quote! { #func_ident }
+    };
+
+    // re-expand the macro whenever one of the resources is modified
+    let tracked: Vec<&Path> = resources
+        .iter()
+        .map(|resource| resource.path.as_path())
+        .chain(partners.iter().flatten().map(|partner| partner.as_path()))
+        .chain(metadata.iter().flat_map(|metadata| metadata.files.iter().map(PathBuf::as_path)))
+        .chain(xfail.iter().map(|list| list.path.as_path()))
+        .collect();
+    let tracking = track_resources(&tracked);
+
+    // a single test iterating all resources, reducing the compile-time of large corpora
+    if let Some(mode) = attrs.aggregate()? {
+        let unsupported = match (&kind, asyncness) {
+            (GenKind::Bench, _) => Some("bench_resources"),
+            (_, Some(_)) => Some("async functions"),
+            _ if attrs.name.is_some() => Some("the parameter `name`"),
+            _ => None,
+        };
+        if let Some(unsupported) = unsupported {
+            return Err(Error::new(
+                mode.span(),
+                format!("aggregate mode is not supported by {}", unsupported),
+            ));
+        }
+
+        // a forwarded `#[should_panic]` expects each resource to panic, rather than any of them
+        let forwarded_panic = forwarded
+            .iter()
+            .find(|attr| attr.path.is_ident("should_panic"))
+            .map(Mark::from_should_panic)
+            .transpose()?;
+        let forwarded: Vec<_> = forwarded
+            .iter()
+            .filter(|attr| !attr.path.is_ident("should_panic"))
+            .collect();
+
+        let mut entries = Vec::new();
+        for (((resource, partner), golden), metadata) in
+            resources.iter().zip(&partners).zip(&goldens).zip(&metadata)
+        {
+            let pattern = &attrs.patterns[resource.pattern];
+            let (ignore_mark, should_panic_mark) = marks_of(resource, metadata);
+            if ignore_mark.is_some_and(|mark| mark.is_set()) {
+                continue;
+            }
+            let unsupported = match (metadata.timeout, metadata.tags.is_empty()) {
+                (Some(_), _) => Some("timeout is"),
+                (None, false) => Some("tags are"),
+                (None, true) => None,
+            };
+            if let Some(unsupported) = unsupported {
+                return Err(Error::new_spanned(
+                    pattern,
+                    format!(
+                        "metadata of {}: {} not supported in aggregate mode",
+                        resource.rel_path.display(),
+                        unsupported
+                    ),
+                ));
+            }
+            let is_xfail = xfail.as_ref().is_some_and(|list| list.contains(resource));
+            let expected_failure = match should_panic_mark {
+                Some(ref mark) if mark.is_set() => Some(mark.text().to_string()),
+                _ if is_xfail => Some(String::new()),
+                _ => forwarded_panic.as_ref().map(|mark| mark.text().to_string()),
+            };
+            entries.push(AggregateEntry {
+                rel_path: slash_path(&resource.rel_path),
+                resource: param.value(&resource.path, pattern)?,
+                partner: match (partner, &attrs.expect) {
+                    (Some(partner), Some(expect)) => Some(partner_param.value(partner, expect)?),
+                    _ => None,
+                },
+                golden: golden.as_ref().map(|golden| golden.display().to_string()),
+                expected_failure,
+            });
+        }
+        let ignored = resources.len() - entries.len();
+
+        let arg = param.convert(quote! { resource });
+        let arg = match attrs.expect {
+            Some(_) => {
+                let partner_arg = partner_param.convert(quote! { partner });
+                quote! { #arg, #partner_arg }
+            }
+            None => arg,
+        };
+        let call = quote! { # func_path ( #arg ) };
+        let call = if fallible { unwrap_error(call) } else { call };
+        let body = match attrs.golden {
+            Some(_) => golden_assertion(call, quote! { golden }),
+            None => quote! { #call; },
+        };
+        let types = AggregateTypes {
+            resource: param.value_type(),
+            partner: attrs.expect.as_ref().map(|_| partner_param.value_type()),
+            golden: attrs.golden.is_some(),
+        };
+        let body = aggregate_loop(&entries, types, body, ignored);
+
+        let test_name = if attrs.module {
+            "aggregate".to_string()
+        } else {
+            format!("{}_aggregate", func_ident)
+        };
+        let test_ident = proc_macro2::Ident::new(&test_name, Span::call_site());
+        let test = quote! {
+            #[ #test_attr ]
+            #( #forwarded )*
+            #[allow(non_snake_case)]
+            fn # test_ident () {
+                #body
+            }
+        };
+        let test = if attrs.module {
+            group_into_module(&func_ident, test)
+        } else {
+            test
+        };
+        return Ok(quote! { #func_copy #test #tracking });
     }
+
+    // the test-name is formed from the path relative to root, independent of the location
+    // of the package, whereas the function is invoked with the absolute path; within a module
+    // named after the function, the test-name is formed from the path only. The parameter
+    // `name` replaces the path by a template, such as `{parent}_{stem}`.
+    let test_idents = unique_idents(
+        resources
+            .iter()
+            .zip(&metadata)
+            .map(|(resource, metadata)| {
+                let rel_path = resource.rel_path.display().to_string();
+                let name = match attrs.name {
+                    Some(ref template) => {
+                        substitute(&template.value(), &resource.name_captures(), template)?
+                    }
+                    None => rel_path.clone(),
+                };
+                let mut test_name = if attrs.module {
+                    name
+                } else {
+                    format!("{}_{}", func_ident, name)
+                };
+
+                // tags are selected by name, for example `cargo test tag_slow`
+                for tag in &metadata.tags {
+                    test_name.push_str("_tag_");
+                    test_name.push_str(tag);
+                }
+
+                // create function name without any delimiter or special character
+                let test_name = canonical_fn_name(&test_name);
+
+                // quote! requires proc_macro2 elements
+                Ok((proc_macro2::Ident::new(&test_name, Span::call_site()), rel_path))
+            })
+            .collect::<Result<_>>()?,
+    )?;
+
+    // for each resource generate a test-function and fold them to single tokenstream
+    let items = resources
+        .iter()
+        .zip(&partners)
+        .zip(&goldens)
+        .zip(&metadata)
+        .zip(test_idents)
+        .map(|((((resource, partner), golden), metadata), test_ident)| {
+            let arg = param.arg(&resource.path, &attrs.patterns[resource.pattern])?;
+            let arg = match (partner, &attrs.expect) {
+                (Some(partner), Some(expect)) => {
+                    let partner_arg = partner_param.arg(partner, expect)?;
+                    quote! { #arg, #partner_arg }
+                }
+                _ => arg,
+            };
+
+            let call = match asyncness {
+                Some(_) => quote! { # func_path ( #arg ).await },
+                None => quote! { # func_path ( #arg ) },
+            };
+
+            let (ignore_mark, should_panic_mark) = marks_of(resource, metadata);
+            let mut marks = Vec::new();
+            marks.extend(ignore_mark.and_then(|mark| mark.ignore_attr()));
+            let should_panic_attr = should_panic_mark.and_then(|mark| mark.should_panic_attr());
+            let panics = should_panic_all || should_panic_attr.is_some();
+            marks.extend(should_panic_attr);
+
+            // a `Result` is returned by the generated test, unless its output is compared
+            // with the golden file, the test is expected to panic, as `should_panic` requires
+            // `()`, or the resource is a known failure; otherwise an error panics
+            let mut returns = fallible && golden.is_none() && !panics;
+            let call = if fallible && !returns { unwrap_error(call) } else { call };
+            let body = match golden {
+                Some(golden) => {
+                    let golden = golden.display().to_string();
+                    golden_assertion(call, quote! { #golden })
+                }
+                None if returns => call,
+                None => quote! { #call; },
+            };
+            let body = match (metadata.timeout, asyncness) {
+                (Some(_), Some(_)) => {
+                    return Err(Error::new_spanned(
+                        &attrs.patterns[resource.pattern],
+                        format!(
+                            "metadata of {}: timeout is not supported by async functions",
+                            resource.rel_path.display()
+                        ),
+                    ))
+                }
+                (Some(timeout_ms), None) => timeout_guard(body, returns, timeout_ms),
+                (None, _) => body,
+            };
+
+            // failures name the resource, as the test-name may be hashed or truncated
+            let pattern = &attrs.patterns[resource.pattern];
+            let context = format!(
+                "function `{}` failed on resource {}, matching the pattern {}",
+                func_ident,
+                resource.rel_path.display(),
+                quote!(#pattern)
+            );
+            let body = match asyncness {
+                Some(_) => async_panic_context(body, &context),
+                None => panic_context(body, &context),
+            };
+
+            // known failures pass if panicking, and fail if succeeding
+            let body = match (&xfail, &attrs.xfail) {
+                (Some(list), Some(lit)) if list.contains(resource) => {
+                    let conflict = if asyncness.is_some() {
+                        Some("async functions are not supported")
+                    } else if panics {
+                        Some("the test is marked `should_panic` already")
+                    } else {
+                        None
+                    };
+                    if let Some(conflict) = conflict {
+                        return Err(Error::new_spanned(
+                            lit,
+                            format!(
+                                "{} is listed in the xfail list, but {}",
+                                resource.rel_path.display(),
+                                conflict
+                            ),
+                        ));
+                    }
+                    let body = xfail_guard(body, returns, &resource.rel_path, &list.path);
+                    returns = false;
+                    body
+                }
+                _ => body,
+            };
+
+            let (output, body) = if returns {
+                (Some(&func_output), error_context(body, &context))
+            } else {
+                (None, body)
+            };
+
+            Ok(match kind {
+                GenKind::Test => quote! {
+                    #[ #test_attr ]
+                    #( #forwarded )*
+                    #( #marks )*
+                    #[allow(non_snake_case)]
+                    #asyncness fn # test_ident () #output {
+                        #body
+                    }
+                },
+                GenKind::Bench => quote! {
+                    #[ #test_attr ]
+                    #( #forwarded )*
+                    #( #marks )*
+                    #[allow(non_snake_case)]
+                    fn # test_ident (b: &mut test::Bencher) {
+                        # func_path ( b, #arg );
+                    }
+                },
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let tests = quote! { #( #items )* };
+    let tests = if attrs.module {
+        group_into_module(&func_ident, tests)
+    } else {
+        tests
+    };
+    Ok(quote! { #func_copy #tests #tracking })
 }
 
 /// Macro generating test-functions, invoking the fn for each item matching the resource-pattern.
@@ -184,6 +651,132 @@ impl Parse for MacroAttributes {
 ///   }
 /// }
 /// ```
+/// The pattern is resolved relative to the directory of the crate's `Cargo.toml`, no matter
+/// the working directory of the compiler. The optional parameter `root` selects a different
+/// base directory:
+///
+/// * `root = "manifest"` - directory of the crate's `Cargo.toml` (default)
+/// * `root = "workspace"` - directory of the workspace's `Cargo.toml`, sharing resources among
+///   all member crates
+/// * `root = "out_dir"` - directory `OUT_DIR` of the crate's build-script
+///
+/// ```ignore
+/// #[test_resources("res/*/input.txt", root = "workspace")]
+/// fn verify_shared_resource(resource: &str) {
+///    assert!(std::path::Path::new(resource).exists());
+/// }
+/// ```
+/// The test-name is formed from the path relative to the base directory, whereas the test-function
+/// is invoked with the absolute path of the resource.
+///
+/// Environment variables of the pattern are expanded, such as `"${OUT_DIR}/fixtures/*.bin"`
+/// for fixtures generated by the build-script; undefined variables are reported as compile
+/// error. If the pattern begins with a variable, the test-name is formed from the path relative
+/// to its value, such as `verify_fixture_fixtures_small_bin`.
+///
+/// Multiple patterns may be given, generating a single test for each resource matched by any of
+/// them. The optional parameter `exclude` takes a pattern or a list of patterns, removing the
+/// resources matching any of them; relative exclude patterns are matched against the path
+/// relative to the base directory.
+///
+/// ```ignore
+/// #[test_resources("res/**/*.json", "extra/*.json", exclude = ["res/broken/**", "res/wip/**"])]
+/// fn verify_json(resource: &str) {
+///    assert!(std::path::Path::new(resource).exists());
+/// }
+/// ```
+///
+/// The optional parameters `case_sensitive`, `require_literal_separator` and
+/// `require_literal_leading_dot` take a boolean, controlling the matching of the patterns like
+/// the options of `glob`, for example `require_literal_leading_dot = true` skipping hidden files.
+/// With the optional parameter `respect_gitignore = true`, resources ignored by `.gitignore` and
+/// `.ignore` files are skipped, such as backups `*.orig` left by editors and merge tools; it
+/// requires the optional feature `gitignore`.
+/// Each include pattern must match at least one resource, otherwise an error is raised.
+///
+/// The resource is passed according to the type of the function's parameter:
+///
+/// * `&std::path::Path`, `std::path::PathBuf` or `&std::path::PathBuf` - the path of the resource
+/// * `&'static str` - the contents of the file, embedded by `include_str!`
+/// * `&'static [u8]` - the contents of the file, embedded by `include_bytes!`
+/// * any other type, such as `&str` or `String` - the path of the resource, converted by `into()`
+///
+/// ```ignore
+/// #[test_resources("res/*/input.txt")]
+/// fn verify_content(content: &'static str) {
+///    assert!(content.starts_with("interface"));
+/// }
+/// ```
+///
+/// A panic of the function is raised again, its message preceded by the function name, the
+/// resource and the pattern it matched. If the function returns a `Result`, the generated test
+/// returns it as well, printing the same context in case of an error.
+///
+/// Placeholders, such as `{case}`, name the text matched at their position of the pattern. The
+/// optional parameter `expect` forms the path of a partner resource from the captured text, passed
+/// as second argument; a missing partner is reported as compile error.
+///
+/// ```ignore
+/// #[test_resources("res/{case}/input.txt", expect = "res/{case}/expect.txt")]
+/// fn verify_pair(input: &'static str, expect: &'static str) {
+///    assert_eq!(parse(input), expect);
+/// }
+/// ```
+///
+/// With the optional parameter `golden`, the function returns its output, such as `String` or
+/// `Vec<u8>`, being compared with the contents of the golden file. Running the tests with the
+/// environment variable `TEST_GENERATOR_BLESS=1` rewrites the golden files instead.
+///
+/// ```ignore
+/// #[test_resources("res/{case}/input.txt", golden = "res/{case}/expect.txt")]
+/// fn verify_output(input: &'static str) -> String {
+///    format!("{:#?}", parse(input))
+/// }
+/// ```
+///
+/// The attributes `#[should_panic]`, `#[ignore]` and `#[cfg(...)]` are copied to each generated
+/// test. The optional parameter `attr` replaces `#[test]`, for example by `tokio::test`, which is
+/// required by async functions; `bench_resources` does not accept async functions.
+///
+/// ```ignore
+/// #[test_resources("res/*/input.txt", attr = tokio::test)]
+/// async fn verify_async(resource: &std::path::Path) {
+///    assert!(tokio::fs::metadata(resource).await.is_ok());
+/// }
+/// ```
+///
+/// The optional parameters `ignore` and `should_panic` take a pattern or a list of patterns,
+/// marking the tests of matching resources only, for example `should_panic = "res/invalid/**"`.
+///
+/// The optional parameter `xfail` names a list of known failures, one resource per line; the
+/// tests of listed resources pass if the function panics, and fail if it succeeds, for example
+/// `xfail = "res/known_failures.txt"`.
+///
+/// With the optional parameter `mode = "aggregate"`, a single test iterates all resources,
+/// failing at the end with a summary of every failing resource, for example
+/// `#[test_resources("corpus/**", mode = "aggregate")]`; a `#[should_panic]` of the function
+/// expects each resource to panic.
+///
+/// The sidecar files `<resource>.toml` and `meta.toml` in the directory of the resource may
+/// specify the keys `ignore`, `should_panic`, `tags` and `timeout` for the test of a resource;
+/// the sidecar files are not tested themselves, even if matching the pattern.
+///
+/// ```ignore
+/// # res/invalid/input.txt.toml
+/// should_panic = "unexpected token"
+/// tags = ["slow"]
+/// timeout = 10
+/// ```
+///
+/// The test-name is formed from the function name and the path, replacing all characters not
+/// permitted in identifiers by `_`. If two resources form the same name, for example
+/// `res/a-b/x.txt` and `res/a_b/x.txt`, each of these names is suffixed with a short hash of
+/// the path, such as `verify_resource_res_a_b_x_txt_60af7a7a`; the suffix is stable between builds.
+/// With the optional parameter `module = true`, the generated tests are grouped into a module
+/// named after the function, such as `verify_resource::res_set1_input_txt`. The optional
+/// parameter `name` forms the test-name from a template instead of the path, for example
+/// `name = "{parent}_{stem}"` producing `verify_resource_set1_input`.
+///
 /// Assuming the following package layout with test file `mytests.rs` and resource folder `res/`,
 /// the output below will be printed on console. The functionality of `build.rs` is explained at crate
 /// [build-deps](https://crates.io/crates/build-deps) and demonstrated with
@@ -193,19 +786,19 @@ impl Parse for MacroAttributes {
 /// ├── build.rs
 /// ├── Cargo.toml
 /// ├── res
-/// │   ├── set1
-/// │   │   ├── expect.txt
-/// │   │   └── input.txt
-/// │   ├── set2
-/// │   │   ├── expect.txt
-/// │   │   └── input.txt
-/// │   └── set3
-/// │       ├── expect.txt
-/// │       └── input.txt
+/// │   ├── set1
+/// │   │   ├── expect.txt
+/// │   │   └── input.txt
+/// │   ├── set2
+/// │   │   ├── expect.txt
+/// │   │   └── input.txt
+/// │   └── set3
+/// │       ├── expect.txt
+/// │       └── input.txt
 /// ├── src
-/// │   └── main.rs
+/// │   └── main.rs
 /// ├── benches
-/// │   └── mybenches.rs
+/// │   └── mybenches.rs
 /// └── tests
 ///     └── mytests.rs
 /// ```
@@ -223,69 +816,21 @@ impl Parse for MacroAttributes {
 /// ```
 #[proc_macro_attribute]
 pub fn test_resources(attrs: TokenStream, func: TokenStream) -> TokenStream {
-    let MacroAttributes { glob_pattern } = parse_macro_input!(attrs as MacroAttributes);
-
-    let pattern = match glob_pattern {
-        Lit::Str(l) => l.value(),
-        Lit::Bool(l) => panic!("expected string parameter, got '{}'", &l.value),
-        Lit::Byte(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        Lit::ByteStr(_) => panic!("expected string parameter, got byte-string"),
-        Lit::Char(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        Lit::Int(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        Lit::Float(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        _ => panic!("expected string parameter"),
-    };
-
-    let func_copy: proc_macro2::TokenStream = func.clone().into();
-
-    let func_ast: ItemFn = syn::parse(func)
-        .expect("failed to parse tokens as a function");
+    let attrs = parse_macro_input!(attrs as MacroAttributes);
 
-    let func_ident = func_ast.ident;
-
-    let paths: Paths = glob(&pattern).expect(&format!("No such file or directory {}", &pattern));
-
-    // for each path generate a test-function and fold them to single tokenstream
-    let result = paths
-        .map(|path| {
-            let path_as_str = path
-                .expect("No such file or directory")
-                .into_os_string()
-                .into_string()
-                .expect("bad encoding");
-            let test_name = format!("{}_{}", func_ident.to_string(), &path_as_str);
-
-            // create function name without any delimiter or special character
-            let test_name = canonical_fn_name(&test_name);
-
-            // quote! requires proc_macro2 elements
-            let test_ident = proc_macro2::Ident::new(&test_name, proc_macro2::Span::call_site());
-
-            let item = quote! {
-                #[test]
-                #[allow(non_snake_case)]
-                fn # test_ident () {
-                    # func_ident ( #path_as_str .into() );
-                }
-            };
-
-            item
-        })
-        .fold((0, func_copy), concat_ts_cnt);
-
-    // panic, the pattern did not match any file or folder
-    if result.0 == 0 {
-        panic!("no resource matching the pattern {}", &pattern);
-    }
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    result.1.into()
+    expand_resources(GenKind::Test, attrs, func)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 /// Macro generating bench-functions, invoking the fn for each item matching the resource-pattern.
 ///
 /// The resource-pattern must not expand to empty list, otherwise an error is raised.
 /// The generated test-functions is a regular bench, being compiled by the rust-compiler; and being
-/// executed in sequentially by the bench-framework.
+/// executed in sequentially by the bench-framework. The patterns are resolved the same way as for
+/// [`test_resources`](attr.test_resources.html), including the optional parameters `root` and
+/// `exclude`; the type of the parameter following the bencher determines the way the resource is
+/// passed.
 /// ```ignore
 /// #![feature(test)] // nightly feature required for API test::Bencher
 ///
@@ -314,19 +859,19 @@ pub fn test_resources(attrs: TokenStream, func: TokenStream) -> TokenStream {
 /// ├── build.rs
 /// ├── Cargo.toml
 /// ├── res
-/// │   ├── set1
-/// │   │   ├── expect.txt
-/// │   │   └── input.txt
-/// │   ├── set2
-/// │   │   ├── expect.txt
-/// │   │   └── input.txt
-/// │   └── set3
-/// │       ├── expect.txt
-/// │       └── input.txt
+/// │   ├── set1
+/// │   │   ├── expect.txt
+/// │   │   └── input.txt
+/// │   ├── set2
+/// │   │   ├── expect.txt
+/// │   │   └── input.txt
+/// │   └── set3
+/// │       ├── expect.txt
+/// │       └── input.txt
 /// ├── src
-/// │   └── main.rs
+/// │   └── main.rs
 /// ├── benches
-/// │   └── mybenches.rs
+/// │   └── mybenches.rs
 /// └── tests
 ///     └── mytests.rs
 /// ```
@@ -342,67 +887,195 @@ pub fn test_resources(attrs: TokenStream, func: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro_attribute]
 pub fn bench_resources(attrs: TokenStream, func: TokenStream) -> TokenStream {
-    let MacroAttributes { glob_pattern } = parse_macro_input!(attrs as MacroAttributes);
-
-    let pattern = match glob_pattern {
-        Lit::Str(l) => l.value(),
-        Lit::Bool(l) => panic!("expected string parameter, got '{}'", &l.value),
-        Lit::Byte(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        Lit::ByteStr(_) => panic!("expected string parameter, got byte-string"),
-        Lit::Char(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        Lit::Int(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        Lit::Float(l) => panic!("expected string parameter, got '{}'", &l.value()),
-        _ => panic!("expected string parameter"),
-    };
+    let attrs = parse_macro_input!(attrs as MacroAttributes);
 
-    let func_copy: proc_macro2::TokenStream = func.clone().into();
+    expand_resources(GenKind::Bench, attrs, func)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-    let func_ast: ItemFn = syn::parse(func)
-        .expect("failed to parse tokens as a function");
+/// Macro generating test-functions, invoking the fn for each of the values.
+///
+/// Each value is an expression, passed to the function as is; the test-name is formed from the
+/// function name and the stringified value, like the names of the deprecated macro
+/// `test_expand_list!`; expressions not being stringified, such as calls, are named by their
+/// position, for example `value_2`. A value may be named by `name => expr`, forming the
+/// test-name from the name instead, for example for values not permitted in identifiers.
+/// ```ignore
+/// #[cfg(test)]
+/// mod tests {
+///   use test_generator::test_values;
+///
+///   #[test_values(10, 100, 1000)]
+///   fn verify_size(size: usize) { assert!(size > 0); }
+///
+///   #[test_values(empty => "", blank => " ", "line break" => "\n")]
+///   fn verify_whitespace(text: &str) { assert!(text.trim().is_empty()); }
+/// }
+/// ```
+/// Producing the tests `verify_size_0000000010`, `verify_size_0000000100`,
+/// `verify_size_0000001000` and `verify_whitespace_empty`, `verify_whitespace_blank`,
+/// `verify_whitespace_line_break`. The attributes `#[should_panic]`, `#[ignore]` and `#[cfg(...)]`
+/// of the function are copied to each test, and a `Result` returned by the function is returned
+/// by the tests as well.
+#[proc_macro_attribute]
+pub fn test_values(attrs: TokenStream, func: TokenStream) -> TokenStream {
+    let values = parse_macro_input!(attrs as ValueList);
 
-    let func_ident = func_ast.ident;
+    expand_cases(GenKind::Test, func, |func_ast| values.cases(&func_ast.ident))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-    let paths: Paths = glob(&pattern).expect(&format!("No such file or directory {}", &pattern));
+/// Macro generating bench-functions, invoking the fn for each of the values.
+///
+/// The values are given the same way as for [`test_values`](attr.test_values.html); each value
+/// is passed to the function following the bencher.
+/// ```ignore
+/// #![feature(test)] // nightly feature required for API test::Bencher
+///
+/// #[cfg(test)]
+/// extern crate test; /* required for test::Bencher */
+///
+/// #[cfg(test)]
+/// mod bench {
+///   use test_generator::bench_values;
+///
+///   #[bench_values(10, 100, 1000)]
+///   fn measure_size(b: &mut test::Bencher, size: usize) {
+///      b.iter(|| vec![0u8; size]);
+///   }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn bench_values(attrs: TokenStream, func: TokenStream) -> TokenStream {
+    let values = parse_macro_input!(attrs as ValueList);
 
-    // for each path generate a test-function and fold them to single tokenstream
-    let result = paths
-        .map(|path| {
-            let path_as_str = path
-                .expect("No such file or directory")
-                .into_os_string()
-                .into_string()
-                .expect("bad encoding");
-            let test_name = format!("{}_{}", func_ident.to_string(), &path_as_str);
-
-            // create function name without any delimiter or special character
-            let test_name = canonical_fn_name(&test_name);
-
-            // quote! requires proc_macro2 elements
-            let test_ident = proc_macro2::Ident::new(&test_name, proc_macro2::Span::call_site());
-
-            let item = quote! {
-                #[bench]
-                #[allow(non_snake_case)]
-                fn # test_ident (b: &mut test::Bencher) {
-                    # func_ident ( b, #path_as_str .into() );
-                }
-            };
+    expand_cases(GenKind::Bench, func, |func_ast| values.cases(&func_ast.ident))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-            item
-        })
-        .fold((0, func_copy), concat_ts_cnt);
+/// Macro generating test-functions, invoking the fn for each combination of resources and
+/// values, the cartesian product of the axes.
+///
+/// Each axis is a parameter of the function, in the same order. The axis `resources` takes a
+/// pattern, resolved relative to the directory of the crate's `Cargo.toml` and passed according
+/// to the type of the parameter like for [`test_resources`](attr.test_resources.html); any other
+/// axis takes a list of expressions, passed as is.
+/// ```ignore
+/// #[cfg(test)]
+/// mod tests {
+///   use test_generator::test_matrix;
+///
+///   #[test_matrix(resources = "res/*/input.txt", level = [1, 5, 9])]
+///   fn verify_compression(input: &'static [u8], level: u32) {
+///      assert_eq!(decompress(&compress(input, level)), input);
+///   }
+/// }
+/// ```
+/// Producing the tests `verify_compression_res_set1_input_txt_level_0000000001`,
+/// `verify_compression_res_set1_input_txt_level_0000000005` and so on, 9 tests for 3 resources.
+#[proc_macro_attribute]
+pub fn test_matrix(attrs: TokenStream, func: TokenStream) -> TokenStream {
+    let matrix = parse_macro_input!(attrs as Matrix);
+
+    let mut tracking = proc_macro2::TokenStream::new();
+    expand_cases(GenKind::Test, func, |func_ast| {
+        let (cases, tracked) = matrix.cases(func_ast)?;
+        tracking = tracked;
+        Ok(cases)
+    })
+    .map(|expanded| quote! { #expanded #tracking })
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
+}
 
-    // panic, the pattern did not match any file or folder
-    if result.0 == 0 {
-        panic!("no resource matching the pattern {}", &pattern);
-    }
+/// Macro generating test-functions, invoking the fn for each row of a table file.
+///
+/// The table is a CSV file, whose first line names the columns, a JSON array of objects, or a
+/// TOML array of tables such as `[[case]]`, located relative to the directory of the crate's
+/// `Cargo.toml`. Each parameter of the function receives the field of the column with the same
+/// name as literal of the parameter's type: `&str` and `String` receive the text of the field,
+/// numeric types the number; strings are converted by `into()`. In CSV files, quoted fields are
+/// strings, whereas unquoted fields passed to other types are numbers or booleans if parsing as
+/// such.
+///
+/// The test-name is formed from the function name and the field of the key column, by default
+/// the first column; the optional parameter `key` names a different one.
+/// ```ignore
+/// // cases/parse.csv:
+/// // name,input,expected
+/// // zero,"0",0
+/// // negative,"-42",-42
+///
+/// #[cfg(test)]
+/// mod tests {
+///   use test_generator::test_cases;
+///
+///   #[test_cases("cases/parse.csv", key = "name")]
+///   fn verify_parse(input: &str, expected: i64) {
+///      assert_eq!(input.parse::<i64>().unwrap(), expected);
+///   }
+/// }
+/// ```
+/// Producing the tests `verify_parse_zero` and `verify_parse_negative`.
+#[proc_macro_attribute]
+pub fn test_cases(attrs: TokenStream, func: TokenStream) -> TokenStream {
+    let table = parse_macro_input!(attrs as TableAttributes);
+
+    let mut tracking = proc_macro2::TokenStream::new();
+    expand_cases(GenKind::Test, func, |func_ast| {
+        let cases = table.cases(func_ast)?;
+        tracking = track_resources(&[table.table_path()?.as_path()]);
+        Ok(cases)
+    })
+    .map(|expanded| quote! { #expanded #tracking })
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
+}
 
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    result.1.into()
+/// Macro generating test-functions, invoking the fn for each section of a file.
+///
+/// The file, located relative to the directory of the crate's `Cargo.toml`, is split into
+/// sections by the lines matching the regular expression `separator`, by default `^--- (\w+)`;
+/// the capture group `name`, or otherwise the first one, names the section. Text preceding the
+/// first separator line is skipped. The function receives the text of the section, and its name
+/// if taking a second parameter; both are converted by `into()`.
+/// ```ignore
+/// // tests/cases.txt:
+/// // --- empty
+/// // --- nested
+/// // ((a))
+///
+/// #[cfg(test)]
+/// mod tests {
+///   use test_generator::test_sections;
+///
+///   #[test_sections("tests/cases.txt", separator = "^--- (\\w+)")]
+///   fn verify_section(text: &str, name: &str) {
+///      assert!(parse(text).is_ok(), "section {}", name);
+///   }
+/// }
+/// ```
+/// Producing the tests `verify_section_empty` and `verify_section_nested`.
+#[proc_macro_attribute]
+pub fn test_sections(attrs: TokenStream, func: TokenStream) -> TokenStream {
+    let sections = parse_macro_input!(attrs as SectionAttributes);
+
+    let mut tracking = proc_macro2::TokenStream::new();
+    expand_cases(GenKind::Test, func, |func_ast| {
+        let cases = sections.cases(func_ast)?;
+        tracking = track_resources(&[sections.file_path()?.as_path()]);
+        Ok(cases)
+    })
+    .map(|expanded| quote! { #expanded #tracking })
+    .unwrap_or_else(|err| err.to_compile_error())
+    .into()
 }
 
 
-/// **Experimental** Helper function encapsulating and unwinding each phase, namely setup, test and teardown
+// **Experimental** Helper function encapsulating and unwinding each phase, namely setup, test and teardown
 //fn run_utest<U, T, D, C>(setup: U, test: T, teardown: D) -> ()
 //    where
 //        U: FnOnce() -> C + std::panic::UnwindSafe,
@@ -435,25 +1108,25 @@ pub fn bench_resources(attrs: TokenStream, func: TokenStream) -> TokenStream {
 //    assert!(finalizer.is_ok());
 //}
 
-/// **Experimental** Executing a 3-phase unit-test: setup, test, teardown
-///
-/// ## Usage
-/// ```
-/// extern crate test_generator;
-///
-/// #[cfg(test)]
-/// mod testsuite {
-///    use test_generator::utest;
-///    use std::fs::File;
-///
-///    struct Context { file: File }
-///
-///    fn setup() -> Context {
-///
-///    }
-/// }
-/// ```
-///
+// **Experimental** Executing a 3-phase unit-test: setup, test, teardown
+//
+// ## Usage
+// ```
+// extern crate test_generator;
+//
+// #[cfg(test)]
+// mod testsuite {
+//    use test_generator::utest;
+//    use std::fs::File;
+//
+//    struct Context { file: File }
+//
+//    fn setup() -> Context {
+//
+//    }
+// }
+// ```
+//
 //#[macro_export]
 //macro_rules! v1_utest {
 //    ( $id: ident, $setup:expr, $test:expr, $teardown:expr ) => {
@@ -490,8 +1163,6 @@ pub fn bench_resources(attrs: TokenStream, func: TokenStream) -> TokenStream {
 // ------------------ deprecated features ------------------
 //
 
-const CONTENT_MAX_LEN: usize = 100;
-
 /// Return the concatenation of two token-streams
 fn concat_ts(
     accu: proc_macro2::TokenStream,
@@ -524,129 +1195,6 @@ impl Parse for GlobExpand {
 /// Prefix for each generated test-function
 const PREFIX: &str = "gen_";
 
-// Compose a new function-identifier from input
-fn fn_ident_from_path(fn_ident: &Ident, path: &PathBuf) -> Ident {
-    let path_as_str = path
-        .clone()
-        .into_os_string()
-        .into_string()
-        .expect("bad encoding");
-
-    // prefixed name & remove delimiters and special characters
-    let stringified = format!("{}_{}", fn_ident.to_string(), &path_as_str);
-
-    // quote! requires proc_macro2 elements
-    let gen_fn_ident = proc_macro2::Ident::new(
-        &canonical_fn_name(&stringified),
-        proc_macro2::Span::call_site(),
-    );
-
-    gen_fn_ident
-}
-
-// Compose a new function-identifier from input
-fn fn_ident_from_string(fn_ident: &Ident, name: &str) -> Ident {
-    // use at most CONTENT_MAX_LEN
-    let safe_len = std::cmp::min(name.len(), CONTENT_MAX_LEN);
-    let safe_name = &name[0..safe_len];
-
-    // prefixed name & remove delimiters and special characters
-    let stringified = format!("{}_{}", fn_ident.to_string(), safe_name);
-    // quote! requires proc_macro2 elements
-    let gen_fn_ident = proc_macro2::Ident::new(
-        &canonical_fn_name(&stringified),
-        proc_macro2::Span::call_site(),
-    );
-
-    gen_fn_ident
-}
-
-// Stringify the expression: arrays are enumerated, identifier-names are embedded
-fn expr_stringified(expr: &Expr, int_as_hex: bool) -> String {
-    let stringified = match expr {
-        Expr::Lit(lit) => match lit {
-            ExprLit {
-                lit: litval,
-                attrs: _,
-            } => match litval {
-                Lit::Int(lit) => {
-                    let val = lit.value();
-                    if int_as_hex {
-                        // if u8-range, use two digits, otherwise 16
-                        if val > 255 {
-                            // not a u8
-                            format!("{:016x}", val)
-                        } else {
-                            format!("{:02x}", val as u8)
-                        }
-                    } else {
-                        format!("{:010}", val)
-                    }
-                }
-                Lit::Char(lit) => {
-                    let val = lit.value();
-                    format!("{}", val)
-                }
-                Lit::Str(lit) => {
-                    let val = lit.value();
-                    val
-                }
-                Lit::Float(lit) => {
-                    let val = lit.value();
-                    format!("{}", val)
-                }
-                _ => panic!(),
-            },
-        },
-        Expr::Array(ref array_expr) => {
-            let elems = &array_expr.elems;
-            let mut composed = String::new();
-            // do not
-            let mut cnt: usize = 0;
-            // concat as hex-numbers, group by 8
-            for expr in elems.iter() {
-                // after 8 elements, always insert '_', do not begin with '_'
-                if cnt > 0 && cnt % 8 == 0 {
-                    composed.push_str("_");
-                }
-                cnt = cnt + 1;
-
-                let expr_str = expr_stringified(&expr, true);
-                composed.push_str(&expr_str);
-            }
-            composed
-        }
-        Expr::Path(ref expr_path) => {
-            let path = &expr_path.path;
-            let leading_colon = path.leading_colon.is_some();
-            let mut composed = String::new();
-
-            for segment in &path.segments {
-                if !composed.is_empty() || leading_colon {
-                    composed.push_str("_")
-                }
-                let ident = &segment.ident;
-                composed.push_str(&ident.to_string());
-            }
-            composed
-        }
-        Expr::Reference(ref reference) => {
-            let ref_expr = &reference.expr;
-
-            expr_stringified(&ref_expr, int_as_hex)
-        }
-        _ => panic!(),
-    };
-    stringified
-}
-
-// Compose a new function-identifier from input
-fn fn_ident_from_expr(fn_ident: &Ident, expr: &Expr) -> Ident {
-    let stringified = expr_stringified(expr, false);
-
-    fn_ident_from_string(fn_ident, &format!("{}", &stringified))
-}
-
 /// **deprecated** Function-Attribute macro expanding glob-file-pattern to a list of directories
 /// and generating a test-function for each one.
 ///
@@ -662,7 +1210,7 @@ fn fn_ident_from_expr(fn_ident: &Ident, expr: &Expr) -> Ident {
 /// The macro will expand the code for each subfolder in `"res/*"`, generating the following
 /// code. This code is not visible in IDE. Every build-time, the code will be newly generated.
 ///
-///```
+///```ignore
 /// #[cfg(test)]
 /// mod tests {
 ///    #[test]
@@ -677,7 +1225,6 @@ fn fn_ident_from_expr(fn_ident: &Ident, expr: &Expr) -> Ident {
 /// }
 ///
 ///```
-
 #[proc_macro]
 pub fn glob_expand(item: TokenStream) -> TokenStream {
     let GlobExpand {
@@ -685,15 +1232,18 @@ pub fn glob_expand(item: TokenStream) -> TokenStream {
         lambda,
     } = parse_macro_input!(item as GlobExpand);
 
-    let pattern = if let Lit::Str(s) = glob_pattern {
-        s.value()
-    } else {
-        panic!();
-    };
+    expand_glob(glob_pattern, lambda)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-    let empty_ts: proc_macro2::TokenStream = "".parse().unwrap();
+/// Implementation of `glob_expand`
+fn expand_glob(glob_pattern: Lit, lambda: Ident) -> Result<proc_macro2::TokenStream> {
+    let pattern = lit_str_value(&glob_pattern)?;
 
-    let paths: Paths = glob(&pattern).expect("Failed to read testdata dir.");
+    let empty_ts = proc_macro2::TokenStream::new();
+
+    let paths = glob_paths(&pattern, &glob_pattern, MatchOptions::new())?;
 
     /// helper, concatting two token-streams
     fn concat(
@@ -703,44 +1253,39 @@ pub fn glob_expand(item: TokenStream) -> TokenStream {
         quote! { # accu # ts }
     }
 
+    // form an identifier with prefix, without delimiters and special characters
+    let func_idents = unique_idents(
+        paths
+            .iter()
+            .map(|path| {
+                let path_as_str = path_to_str(path, &glob_pattern)?;
+                let func_name = canonical_fn_name(&format!("{}{}", PREFIX, path_as_str));
+
+                // quote! requires proc_macro2 elements
+                let func_ident = proc_macro2::Ident::new(&func_name, Span::call_site());
+                Ok((func_ident, path_as_str.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    )?;
+
     // for each path generate a test-function and fold them to single tokenstream
-    let result = paths
-        .map(|path| {
-            let path_as_str = path
-                .expect("No such file or directory")
-                .into_os_string()
-                .into_string()
-                .expect("bad encoding");
-
-            // remove delimiters and special characters
-            let canonical_name = path_as_str
-                .replace("\"", " ")
-                .replace(" ", "_")
-                .replace("-", "_")
-                .replace("*", "_")
-                .replace("/", "_");
-
-            // form an identifier with prefix
-            let mut func_name = PREFIX.to_string();
-            func_name.push_str(&canonical_name);
-
-            // quote! requires proc_macro2 elements
-            let func_ident = proc_macro2::Ident::new(&func_name, proc_macro2::Span::call_site());
-
-            let item = quote! {
+    let items = paths
+        .iter()
+        .zip(func_idents)
+        .map(|(path, func_ident)| {
+            let path_as_str = path_to_str(path, &glob_pattern)?;
+
+            Ok(quote! {
                 # [test]
                 fn # func_ident () {
                     let f = #lambda;
                     f( #path_as_str );
                 }
-            };
-
-            item
+            })
         })
-        .fold(empty_ts, concat);
+        .collect::<Result<Vec<_>>>()?;
 
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    result.into()
+    Ok(items.into_iter().fold(empty_ts, concat))
 }
 
 /// Parser elements
@@ -774,7 +1319,7 @@ impl Parse for ExpandPaths {
 /// }
 /// ```
 /// Assuming  `"res/*"` expands to "res/set1", and "res/set2" the macro will expand to
-///```
+///```ignore
 /// mod tests {
 ///    #[test]
 ///    fn test_exists_res_set1() {
@@ -794,40 +1339,55 @@ pub fn test_expand_paths(item: TokenStream) -> TokenStream {
         glob_pattern,
     } = parse_macro_input!(item as ExpandPaths);
 
-    let pattern = if let Lit::Str(s) = glob_pattern {
-        s.value()
-    } else {
-        panic!();
-    };
-
-    let empty_ts: proc_macro2::TokenStream = "".parse().unwrap();
-
-    let paths: Paths = glob(&pattern).expect("Invalid 'paths' pattern.");
+    expand_paths(GenKind::Test, fn_ident, glob_pattern)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
 
-    // for each path generate a test-function and fold them to single tokenstream
-    let result = paths
-        .map(|path| {
-            // check for error, shadow the name
-            let path = path.expect("No such file or directory");
+/// Implementation of `test_expand_paths` and `bench_expand_paths`
+fn expand_paths(kind: GenKind, fn_ident: Ident, glob_pattern: Lit) -> Result<proc_macro2::TokenStream> {
+    let pattern = lit_str_value(&glob_pattern)?;
 
-            // form a function identifier, each path is unique => no index required
-            let gen_fn_ident = fn_ident_from_path(&fn_ident, &path);
+    let empty_ts = proc_macro2::TokenStream::new();
 
-            let path_as_str = path.into_os_string().into_string().expect("bad encoding");
+    let paths = glob_paths(&pattern, &glob_pattern, MatchOptions::new())?;
 
-            let item = quote! {
-                # [test]
-                fn #gen_fn_ident () {
-                    #fn_ident ( #path_as_str );
-                }
-            };
+    // form a function identifier for each path
+    let gen_fn_idents = unique_idents(
+        paths
+            .iter()
+            .map(|path| {
+                let gen_fn_ident = fn_ident_from_path(&fn_ident, path, &glob_pattern)?;
+                Ok((gen_fn_ident, path.display().to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    )?;
 
-            item
+    // for each path generate a test-function and fold them to single tokenstream
+    let items = paths
+        .iter()
+        .zip(gen_fn_idents)
+        .map(|(path, gen_fn_ident)| {
+            let path_as_str = path_to_str(path, &glob_pattern)?;
+
+            Ok(match kind {
+                GenKind::Test => quote! {
+                    # [test]
+                    fn #gen_fn_ident () {
+                        #fn_ident ( #path_as_str );
+                    }
+                },
+                GenKind::Bench => quote! {
+                    # [bench]
+                    fn #gen_fn_ident (bencher: & mut test::Bencher) {
+                        #fn_ident (bencher, #path_as_str );
+                    }
+                },
+            })
         })
-        .fold(empty_ts, concat_ts);
+        .collect::<Result<Vec<_>>>()?;
 
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    result.into()
+    Ok(items.into_iter().fold(empty_ts, concat_ts))
 }
 
 /// **deprecated** Generate a benchmark-function call for each file matching the pattern
@@ -865,40 +1425,9 @@ pub fn bench_expand_paths(item: TokenStream) -> TokenStream {
         glob_pattern,
     } = parse_macro_input!(item as ExpandPaths);
 
-    let pattern = if let Lit::Str(s) = glob_pattern {
-        s.value()
-    } else {
-        panic!();
-    };
-
-    let empty_ts: proc_macro2::TokenStream = "".parse().unwrap();
-
-    let paths: Paths = glob(&pattern).expect("Invalid 'paths' pattern.");
-
-    // for each path generate a test-function and fold them to single tokenstream
-    let result = paths
-        .map(|path| {
-            // check for error, shadow the name
-            let path = path.expect("No such file or directory");
-
-            // form a function identifier, each path is unique => no index required
-            let gen_fn_ident = fn_ident_from_path(&fn_ident, &path);
-
-            let path_as_str = path.into_os_string().into_string().expect("bad encoding");
-
-            let item = quote! {
-                # [bench]
-                fn #gen_fn_ident (bencher: & mut test::Bencher) {
-                    #fn_ident (bencher, #path_as_str );
-                }
-            };
-
-            item
-        })
-        .fold(empty_ts, concat_ts);
-
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    result.into()
+    expand_paths(GenKind::Bench, fn_ident, glob_pattern)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
 
 /// Parser elements
@@ -918,7 +1447,8 @@ impl Parse for ExpandList {
     }
 }
 
-/// **deprecated** Generate a test-function call for each list-element
+/// **deprecated** Generate a test-function call for each list-element, superseded by the
+/// attribute [`test_values`](attr.test_values.html)
 /// ```
 /// extern crate test_generator;
 /// #[cfg(test)]
@@ -938,7 +1468,7 @@ impl Parse for ExpandList {
 /// }
 /// ```
 /// Will expand to test-functions incorporating the array-elements
-///```
+///```ignore
 /// #[cfg(test)]
 /// mod tests {
 ///    #[test]
@@ -966,40 +1496,67 @@ impl Parse for ExpandList {
 pub fn test_expand_list(item: TokenStream) -> TokenStream {
     let ExpandList { fn_ident, listing } = parse_macro_input!(item as ExpandList);
 
+    expand_list(GenKind::Test, fn_ident, listing)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Implementation of `test_expand_list` and `bench_expand_list`
+fn expand_list(kind: GenKind, fn_ident: Ident, listing: Expr) -> Result<proc_macro2::TokenStream> {
     let expr_array = if let Expr::Array(expr_array) = listing {
         expr_array
     } else {
-        panic!();
+        return Err(Error::new_spanned(
+            listing,
+            "expected array of values, for example [ 10, 100, 1000 ]",
+        ));
     };
 
-    let empty_ts: proc_macro2::TokenStream = "".parse().unwrap();
+    let empty_ts = proc_macro2::TokenStream::new();
 
     let elems: syn::punctuated::Punctuated<Expr, _> = expr_array.elems;
 
-    let item = elems
+    // form a function identifier for each element, distinct elements may form the same name
+    let gen_fn_idents = unique_idents(
+        elems
+            .iter()
+            .map(|expr| Ok((fn_ident_from_expr(&fn_ident, expr)?, quote!(#expr).to_string())))
+            .collect::<Result<Vec<_>>>()?,
+    )?;
+
+    let items = elems
         .iter()
-        .map(|expr| {
-            let gen_fn_ident = fn_ident_from_expr(&fn_ident, expr);
-            let ref_symbol_ts: proc_macro2::TokenStream = match expr {
-                Expr::Reference(_) => "".parse().unwrap(),
-                _ => "&".parse().unwrap(),
+        .zip(gen_fn_idents)
+        .map(|(expr, gen_fn_ident)| {
+            let ref_symbol_ts = match expr {
+                Expr::Reference(_) => quote! {},
+                _ => quote! { & },
             };
 
-            quote! {
-                #[test]
-                fn #gen_fn_ident() {
-                    let local = #ref_symbol_ts #expr;
-                    #fn_ident ( local );
-                }
-            }
+            Ok(match kind {
+                GenKind::Test => quote! {
+                    #[test]
+                    fn #gen_fn_ident() {
+                        let local = #ref_symbol_ts #expr;
+                        #fn_ident ( local );
+                    }
+                },
+                GenKind::Bench => quote! {
+                    # [bench]
+                    fn #gen_fn_ident (bencher: & mut test::Bencher) {
+                        let local = #ref_symbol_ts #expr;
+                        #fn_ident (bencher, local );
+                    }
+                },
+            })
         })
-        .fold(empty_ts, concat_ts);
+        .collect::<Result<Vec<_>>>()?;
 
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    item.into()
+    Ok(items.into_iter().fold(empty_ts, concat_ts))
 }
 
-/// **deprecated** Generate a benchmark-function call for each list-element
+/// **deprecated** Generate a benchmark-function call for each list-element, superseded by the
+/// attribute [`bench_values`](attr.bench_values.html)
 /// ```
 /// extern crate test_generator;
 /// #[cfg(test)]
@@ -1034,35 +1591,7 @@ pub fn test_expand_list(item: TokenStream) -> TokenStream {
 pub fn bench_expand_list(item: TokenStream) -> TokenStream {
     let ExpandList { fn_ident, listing } = parse_macro_input!(item as ExpandList);
 
-    let expr_array = if let Expr::Array(expr_array) = listing {
-        expr_array
-    } else {
-        panic!();
-    };
-
-    let empty_ts: proc_macro2::TokenStream = "".parse().unwrap();
-
-    let elems: syn::punctuated::Punctuated<Expr, _> = expr_array.elems;
-
-    let item = elems
-        .iter()
-        .map(|expr| {
-            let gen_fn_ident = fn_ident_from_expr(&fn_ident, expr);
-            let ref_symbol_ts: proc_macro2::TokenStream = match expr {
-                Expr::Reference(_) => "".parse().unwrap(),
-                _ => "&".parse().unwrap(),
-            };
-
-            quote! {
-                # [bench]
-                fn #gen_fn_ident (bencher: & mut test::Bencher) {
-                    let local = #ref_symbol_ts #expr;
-                    #fn_ident (bencher, local );
-                }
-            }
-        })
-        .fold(empty_ts, concat_ts);
-
-    // transforming proc_macro2::TokenStream into proc_macro::TokenStream
-    item.into()
+    expand_list(GenKind::Bench, fn_ident, listing)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
@@ -0,0 +1,178 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Parameters of the attribute `test_matrix`, the cartesian product of resources and values
+
+use glob::MatchOptions;
+use proc_macro2::{Ident, Span};
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, Error, Expr, ItemFn, Lit, LitStr, Token};
+
+use crate::cases::Case;
+use crate::naming::{canonical_fn_name, expr_name};
+use crate::params::ResourceParam;
+use crate::resources::{collect_resources, track_resources, ResourceRoot};
+
+/// Axis of the matrix, a parameter of the annotated function
+enum Axis {
+    /// Resources matching the pattern, such as `resources = "res/*/input.txt"`
+    Resources(LitStr),
+    /// Values, such as `level = [1, 5, 9]`
+    Values(Ident, Vec<Expr>),
+}
+
+/// Axis parser, the name followed by `=` and either a pattern, if named `resources`, or a
+/// bracketed list of expressions
+impl Parse for Axis {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        if name == "resources" {
+            return Ok(Axis::Resources(input.parse()?));
+        }
+        let content;
+        bracketed!(content in input);
+        let values = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+        if values.is_empty() {
+            return Err(Error::new(
+                name.span(),
+                format!("axis '{}' has no values, expected a list such as [1, 5, 9]", name),
+            ));
+        }
+        Ok(Axis::Values(name, values.into_iter().collect()))
+    }
+}
+
+/// Element of an axis, the part of the test-name, the argument and its description
+struct Element {
+    name: String,
+    arg: proc_macro2::TokenStream,
+    description: String,
+}
+
+/// Axes of the attribute `test_matrix`, in the order of the parameters of the function
+pub(crate) struct Matrix {
+    axes: Vec<Axis>,
+}
+
+impl Matrix {
+    /// A case for each combination of the elements of the axes, and the tokens registering the
+    /// resources as dependencies of the compilation unit. The test-name is formed from the
+    /// function name, the path of the resource and the name and stringified value of each axis.
+    pub(crate) fn cases(&self, func: &ItemFn) -> Result<(Vec<Case>, proc_macro2::TokenStream)> {
+        let root_dir = ResourceRoot::Manifest.dir(Span::call_site())?;
+        let mut tracked = Vec::new();
+        let mut axes = Vec::with_capacity(self.axes.len());
+        for (index, axis) in self.axes.iter().enumerate() {
+            let elements = match axis {
+                Axis::Resources(pattern) => {
+                    let pattern_lit = Lit::Str(pattern.clone());
+                    let param = ResourceParam::from_fn(func, index);
+                    let resources = collect_resources(
+                        &root_dir,
+                        &[pattern_lit],
+                        &[],
+                        MatchOptions::new(),
+                        false,
+                    )?;
+                    let elements = resources
+                        .iter()
+                        .map(|resource| {
+                            Ok(Element {
+                                name: resource.rel_path.display().to_string(),
+                                arg: param.arg(&resource.path, pattern)?,
+                                description: format!(
+                                    "resource {}",
+                                    resource.rel_path.display()
+                                ),
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    tracked.extend(resources.into_iter().map(|resource| resource.path));
+                    elements
+                }
+                Axis::Values(name, values) => values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, expr)| {
+                        Ok(Element {
+                            name: format!("{}_{}", name, expr_name(expr, index)),
+                            arg: quote! { #expr },
+                            description: format!("{} = {}", name, quote!(#expr)),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            };
+            axes.push(elements);
+        }
+
+        // cartesian product, the last axis varying fastest
+        let mut combinations: Vec<Vec<&Element>> = vec![Vec::new()];
+        for elements in &axes {
+            combinations = combinations
+                .iter()
+                .flat_map(|combination| {
+                    elements.iter().map(move |element| {
+                        let mut combination = combination.clone();
+                        combination.push(element);
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        let func_ident = &func.ident;
+        let cases = combinations
+            .into_iter()
+            .map(|combination| {
+                let names: Vec<&str> = combination.iter().map(|e| e.name.as_str()).collect();
+                let args = combination.iter().map(|e| &e.arg);
+                let descriptions: Vec<&str> =
+                    combination.iter().map(|e| e.description.as_str()).collect();
+                let test_name = format!("{}_{}", func_ident, names.join("_"));
+                Case {
+                    ident: Ident::new(&canonical_fn_name(&test_name), Span::call_site()),
+                    origin: names.join(", "),
+                    args: quote! { #( #args ),* },
+                    context: format!(
+                        "function `{}` failed on {}",
+                        func_ident,
+                        descriptions.join(", ")
+                    ),
+                }
+            })
+            .collect();
+
+        let tracked: Vec<&std::path::Path> = tracked.iter().map(|path| path.as_path()).collect();
+        Ok((cases, track_resources(&tracked)))
+    }
+}
+
+/// Matrix parser, a comma separated list of axes, at least one
+impl Parse for Matrix {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Err(input.error(
+                "expected at least one axis, such as `resources = \"res/*/input.txt\", level = [1, 5, 9]`",
+            ));
+        }
+        let axes = Punctuated::<Axis, Token![,]>::parse_terminated(input)?;
+        let mut names: Vec<String> = Vec::new();
+        for axis in &axes {
+            let (name, span) = match axis {
+                Axis::Resources(pattern) => ("resources".to_string(), pattern.span()),
+                Axis::Values(name, _) => (name.to_string(), name.span()),
+            };
+            if names.contains(&name) {
+                return Err(Error::new(span, format!("axis '{}' is given twice", name)));
+            }
+            names.push(name);
+        }
+        Ok(Matrix {
+            axes: axes.into_iter().collect(),
+        })
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Sidecar metadata files, controlling the test generated for a resource
+
+use quote::{quote, ToTokens};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use syn::parse::Result;
+use syn::{Attribute, Error, Lit, Meta, MetaNameValue, NestedMeta};
+use toml::Value;
+
+/// Name of the sidecar file applying to all resources of a directory
+const DIR_METADATA: &str = "meta.toml";
+
+/// Value of the keys `ignore` and `should_panic`, either a flag or a text, namely the reason
+/// for ignoring the test, or the expected panic message
+#[derive(Clone)]
+pub(crate) enum Mark {
+    Flag(bool),
+    Text(String),
+}
+
+impl Mark {
+    /// Return true unless the flag is `false`
+    pub(crate) fn is_set(&self) -> bool {
+        match self {
+            Mark::Flag(flag) => *flag,
+            Mark::Text(_) => true,
+        }
+    }
+
+    /// The text, empty for flags
+    pub(crate) fn text(&self) -> &str {
+        match self {
+            Mark::Flag(_) => "",
+            Mark::Text(text) => text,
+        }
+    }
+
+    /// Mark of the attribute `#[should_panic]`, `#[should_panic = "message"]` or
+    /// `#[should_panic(expected = "message")]`
+    pub(crate) fn from_should_panic(attr: &Attribute) -> Result<Mark> {
+        let expected = match attr.parse_meta()? {
+            Meta::Word(_) => return Ok(Mark::Flag(true)),
+            Meta::NameValue(MetaNameValue { lit: Lit::Str(lit), .. }) => Some(lit),
+            Meta::List(list) => match list.nested.iter().next() {
+                Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    ident,
+                    lit: Lit::Str(lit),
+                    ..
+                }))) if ident == "expected" && list.nested.len() == 1 => Some(lit.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        match expected {
+            Some(lit) => Ok(Mark::Text(lit.value())),
+            None => Err(Error::new_spanned(
+                attr,
+                "expected `#[should_panic]` or `#[should_panic(expected = \"message\")]`",
+            )),
+        }
+    }
+
+    /// Attribute `#[ignore]`, if set
+    pub(crate) fn ignore_attr(&self) -> Option<proc_macro2::TokenStream> {
+        match self {
+            Mark::Flag(true) => Some(quote! { #[ignore] }),
+            Mark::Flag(false) => None,
+            Mark::Text(reason) => Some(quote! { #[ignore = #reason] }),
+        }
+    }
+
+    /// Attribute `#[should_panic]`, if set
+    pub(crate) fn should_panic_attr(&self) -> Option<proc_macro2::TokenStream> {
+        match self {
+            Mark::Flag(true) => Some(quote! { #[should_panic] }),
+            Mark::Flag(false) => None,
+            Mark::Text(expected) => Some(quote! { #[should_panic(expected = #expected)] }),
+        }
+    }
+}
+
+/// Metadata of a resource, read from the sidecar files `<resource>.toml` and `meta.toml` in
+/// the directory of the resource; the keys of the former take precedence.
+#[derive(Default)]
+pub(crate) struct Metadata {
+    /// Mark the test `#[ignore]`, `ignore = true` or `ignore = "reason"`
+    pub(crate) ignore: Option<Mark>,
+    /// Mark the test `#[should_panic]`, `should_panic = true` or `should_panic = "message"`
+    pub(crate) should_panic: Option<Mark>,
+    /// Tags appended to the test-name, `tags = ["slow"]`
+    pub(crate) tags: Vec<String>,
+    /// Fail the test if not finished in time, `timeout = 10` in seconds
+    pub(crate) timeout: Option<u64>,
+    /// Sidecar files read, being tracked as dependencies
+    pub(crate) files: Vec<PathBuf>,
+}
+
+/// Sidecar files of the resource, in increasing order of precedence
+fn sidecar_paths(resource: &Path) -> Vec<PathBuf> {
+    let mut resource_metadata = resource.as_os_str().to_owned();
+    resource_metadata.push(".toml");
+    let mut paths: Vec<PathBuf> = resource
+        .parent()
+        .map(|dir| dir.join(DIR_METADATA))
+        .into_iter()
+        .collect();
+    paths.push(PathBuf::from(resource_metadata));
+    paths
+}
+
+/// Return true if the path is a sidecar file, either `meta.toml` or `<resource>.toml` next to
+/// the resource; these are not resources of their own
+pub(crate) fn is_sidecar(path: &Path) -> bool {
+    if path.file_name() == Some(OsStr::new(DIR_METADATA)) {
+        return true;
+    }
+    match path.to_str().and_then(|path| path.strip_suffix(".toml")) {
+        Some(resource) => Path::new(resource).is_file(),
+        None => false,
+    }
+}
+
+/// Return the value as mark, a boolean or a string
+fn mark_value(value: &Value) -> Option<Mark> {
+    match value {
+        Value::Boolean(flag) => Some(Mark::Flag(*flag)),
+        Value::String(text) => Some(Mark::Text(text.clone())),
+        _ => None,
+    }
+}
+
+/// Return the value as tags, a list of non-empty strings
+fn tags_value(value: &Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|tag| tag.as_str().filter(|tag| !tag.is_empty()).map(str::to_string))
+        .collect()
+}
+
+/// Return the value as timeout in milliseconds, a positive number of seconds
+fn timeout_value(value: &Value) -> Option<u64> {
+    let seconds = match value {
+        Value::Integer(seconds) => *seconds as f64,
+        Value::Float(seconds) => *seconds,
+        _ => return None,
+    };
+    if seconds > 0.0 && seconds.is_finite() {
+        Some((seconds * 1000.0).ceil() as u64)
+    } else {
+        None
+    }
+}
+
+impl Metadata {
+    /// Read the sidecar files of the resource, if any; errors are reported at the given tokens,
+    /// naming the offending file
+    pub(crate) fn read<T: ToTokens>(resource: &Path, spanned: &T) -> Result<Metadata> {
+        let mut metadata = Metadata::default();
+        for path in sidecar_paths(resource) {
+            if path == resource || !path.is_file() {
+                continue;
+            }
+            let error = |msg: String| {
+                Error::new_spanned(spanned, format!("metadata {}: {}", path.display(), msg))
+            };
+            let content = std::fs::read_to_string(&path).map_err(|err| error(err.to_string()))?;
+            let table = match content.parse::<Value>() {
+                Ok(Value::Table(table)) => table,
+                Ok(_) => return Err(error("expected a table".to_string())),
+                Err(err) => return Err(error(err.to_string())),
+            };
+            for (key, value) in &table {
+                let invalid = || error(format!("invalid value of '{}': {}", key, value));
+                match key.as_str() {
+                    "ignore" => metadata.ignore = Some(mark_value(value).ok_or_else(invalid)?),
+                    "should_panic" => {
+                        metadata.should_panic = Some(mark_value(value).ok_or_else(invalid)?)
+                    }
+                    "tags" => metadata.tags = tags_value(value).ok_or_else(invalid)?,
+                    "timeout" => metadata.timeout = Some(timeout_value(value).ok_or_else(invalid)?),
+                    other => {
+                        return Err(error(format!(
+                            "unknown key '{}', expected 'ignore', 'should_panic', 'tags' or 'timeout'",
+                            other
+                        )))
+                    }
+                }
+            }
+            metadata.files.push(path);
+        }
+        Ok(metadata)
+    }
+}
+
+/// Statements running the body in a separate thread, failing if not finished within the
+/// timeout; the value of the body is returned, and a panic of the body is propagated unchanged,
+/// so `should_panic` is not affected. The error of a fallible body, such as `Box<dyn Error>`,
+/// need not be `Send`: it is formatted within the thread, and the test panics with it.
+pub(crate) fn timeout_guard(
+    body: proc_macro2::TokenStream,
+    fallible: bool,
+    timeout_ms: u64,
+) -> proc_macro2::TokenStream {
+    let (body, result) = if fallible {
+        (
+            quote! {
+                match { #body } {
+                    Ok(output) => Ok(output),
+                    Err(err) => Err(format!("{:?}", err)),
+                }
+            },
+            quote! {
+                match result {
+                    Ok(output) => Ok(output),
+                    Err(err) => panic!("{}", err),
+                }
+            },
+        )
+    } else {
+        (body, quote! { result })
+    };
+    quote! {
+        let (finished, done) = std::sync::mpsc::channel::<()>();
+        let worker = std::thread::spawn(move || {
+            let result = { #body };
+            let _ = finished.send(());
+            result
+        });
+        let timeout = std::time::Duration::from_millis(#timeout_ms);
+        if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = done.recv_timeout(timeout) {
+            panic!("test exceeded the timeout of {:?}", timeout);
+        }
+        match worker.join() {
+            Ok(result) => #result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Forming the identifiers of the generated functions
+
+use proc_macro2::{Ident, Span};
+use std::path::Path;
+use syn::parse::Result;
+use syn::{Error, Expr, ExprLit, ExprUnary, Lit, UnOp};
+use test_generator_common::naming::{canonical_name, unique_names};
+
+use crate::resources::path_to_str;
+
+/// Maximal number of characters taken from an expression to form a function name
+const CONTENT_MAX_LEN: usize = 100;
+
+/// Form canonical name without any punctuation/delimiter or special character
+pub(crate) fn canonical_fn_name(s: &str) -> String {
+    canonical_name(s)
+}
+
+/// Ensure the identifiers are unique, see `test_generator_common::naming::unique_names`; each
+/// candidate is a pair of the identifier and the origin it has been formed from, for example the
+/// resource path. If the suffixed identifiers still collide, an error naming both origins is
+/// returned.
+pub(crate) fn unique_idents(candidates: Vec<(Ident, String)>) -> Result<Vec<Ident>> {
+    let names = candidates
+        .iter()
+        .map(|(ident, origin)| (ident.to_string(), origin.clone()))
+        .collect::<Vec<_>>();
+    let names = unique_names(&names).map_err(|err| {
+        Error::new(
+            candidates[err.index].0.span(),
+            format!(
+                "generated function name `{}` is ambiguous, formed from both {:?} and {:?}",
+                err.name, err.first, err.second
+            ),
+        )
+    })?;
+    Ok(candidates
+        .iter()
+        .zip(names)
+        .map(|((ident, _), name)| Ident::new(&name, ident.span()))
+        .collect())
+}
+
+// Compose a new function-identifier from input
+pub(crate) fn fn_ident_from_path(fn_ident: &Ident, path: &Path, pattern: &Lit) -> Result<Ident> {
+    let path_as_str = path_to_str(path, pattern)?;
+
+    // prefixed name & remove delimiters and special characters
+    let stringified = format!("{}_{}", fn_ident, path_as_str);
+
+    // quote! requires proc_macro2 elements
+    Ok(Ident::new(
+        &canonical_fn_name(&stringified),
+        Span::call_site(),
+    ))
+}
+
+// Compose a new function-identifier from input
+pub(crate) fn fn_ident_from_string(fn_ident: &Ident, name: &str) -> Ident {
+    // use at most CONTENT_MAX_LEN characters, not splitting multi-byte characters
+    let safe_name: String = name.chars().take(CONTENT_MAX_LEN).collect();
+
+    // prefixed name & remove delimiters and special characters
+    let stringified = format!("{}_{}", fn_ident, safe_name);
+    // quote! requires proc_macro2 elements
+    Ident::new(
+        &canonical_fn_name(&stringified),
+        Span::call_site(),
+    )
+}
+
+// Stringify the expression: arrays are enumerated, identifier-names are embedded
+pub(crate) fn expr_stringified(expr: &Expr, int_as_hex: bool) -> Result<String> {
+    let stringified = match expr {
+        Expr::Lit(ExprLit { lit: litval, .. }) => match litval {
+            Lit::Int(lit) => {
+                let val = lit.value();
+                if int_as_hex {
+                    // if u8-range, use two digits, otherwise 16
+                    if val > 255 {
+                        // not a u8
+                        format!("{:016x}", val)
+                    } else {
+                        format!("{:02x}", val as u8)
+                    }
+                } else {
+                    format!("{:010}", val)
+                }
+            }
+            Lit::Char(lit) => {
+                let val = lit.value();
+                format!("{}", val)
+            }
+            Lit::Str(lit) => lit.value(),
+            Lit::Float(lit) => {
+                let val = lit.value();
+                format!("{}", val)
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    litval,
+                    "unsupported literal, expected integer, float, char or string",
+                ))
+            }
+        },
+        Expr::Array(ref array_expr) => {
+            let elems = &array_expr.elems;
+            let mut composed = String::new();
+            // concat as hex-numbers, group by 8
+            for (cnt, expr) in elems.iter().enumerate() {
+                // after 8 elements, always insert '_', do not begin with '_'
+                if cnt > 0 && cnt % 8 == 0 {
+                    composed.push('_');
+                }
+
+                let expr_str = expr_stringified(expr, true)?;
+                composed.push_str(&expr_str);
+            }
+            composed
+        }
+        Expr::Path(ref expr_path) => {
+            let path = &expr_path.path;
+            let leading_colon = path.leading_colon.is_some();
+            let mut composed = String::new();
+
+            for segment in &path.segments {
+                if !composed.is_empty() || leading_colon {
+                    composed.push('_')
+                }
+                let ident = &segment.ident;
+                composed.push_str(&ident.to_string());
+            }
+            composed
+        }
+        Expr::Reference(ref reference) => {
+            let ref_expr = &reference.expr;
+
+            expr_stringified(ref_expr, int_as_hex)?
+        }
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr: ref operand,
+            ..
+        }) => format!("neg_{}", expr_stringified(operand, int_as_hex)?),
+        _ => {
+            return Err(Error::new_spanned(
+                expr,
+                "unsupported expression, expected literal, array, path, reference or negation",
+            ))
+        }
+    };
+    Ok(stringified)
+}
+
+/// Name of the value expression: the stringified expression, or the position of the value
+/// counting from 1 for expressions not being stringified, such as calls or operators; the
+/// expression is passed as is, so naming it must not fail.
+pub(crate) fn expr_name(expr: &Expr, index: usize) -> String {
+    expr_stringified(expr, false).unwrap_or_else(|_| format!("value_{}", index + 1))
+}
+
+// Compose a new function-identifier from input
+pub(crate) fn fn_ident_from_expr(fn_ident: &Ident, expr: &Expr) -> Result<Ident> {
+    let stringified = expr_stringified(expr, false)?;
+
+    Ok(fn_ident_from_string(fn_ident, &stringified))
+}
@@ -0,0 +1,149 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Passing the resource to the annotated function, according to the type of the parameter
+
+use quote::{quote, ToTokens};
+use std::path::Path;
+use syn::parse::Result;
+use syn::{Error, FnArg, ItemFn, ReturnType, Type};
+
+/// The way the resource is passed to the annotated function
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ResourceParam {
+    /// Path as string literal, converted by `into()`, for example into `&str` or `String`
+    Into,
+    /// Path as `&std::path::Path`
+    Path,
+    /// Path as `std::path::PathBuf`
+    PathBuf,
+    /// Path as `&std::path::PathBuf`
+    PathBufRef,
+    /// File contents as `&'static [u8]`, embedded by `include_bytes!`
+    Bytes,
+    /// File contents as `&'static str`, embedded by `include_str!`
+    Str,
+}
+
+/// Return the identifier of the last path segment, for example `PathBuf` of `std::path::PathBuf`
+pub(crate) fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => type_path
+            .path
+            .segments
+            .iter()
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        Type::Paren(paren) => type_name(&paren.elem),
+        Type::Group(group) => type_name(&group.elem),
+        _ => None,
+    }
+}
+
+impl ResourceParam {
+    /// Derive the way of passing the resource from the type of the parameter
+    pub(crate) fn from_type(ty: &Type) -> ResourceParam {
+        match ty {
+            Type::Reference(reference) => {
+                let is_static = reference
+                    .lifetime
+                    .as_ref()
+                    .is_some_and(|lifetime| lifetime.ident == "static");
+                match &*reference.elem {
+                    Type::Slice(slice) if type_name(&slice.elem).as_deref() == Some("u8") => {
+                        ResourceParam::Bytes
+                    }
+                    elem => match type_name(elem).as_deref() {
+                        // the plain `&str` is the path, keeping compatibility with former releases
+                        Some("str") if is_static => ResourceParam::Str,
+                        Some("Path") => ResourceParam::Path,
+                        Some("PathBuf") => ResourceParam::PathBufRef,
+                        _ => ResourceParam::Into,
+                    },
+                }
+            }
+            ty => match type_name(ty).as_deref() {
+                Some("PathBuf") => ResourceParam::PathBuf,
+                _ => ResourceParam::Into,
+            },
+        }
+    }
+
+    /// Derive the way of passing the resource from the parameter at position `index`;
+    /// the default conversion `into()` is used if the function has no such parameter.
+    pub(crate) fn from_fn(func: &ItemFn, index: usize) -> ResourceParam {
+        match func.decl.inputs.iter().nth(index) {
+            Some(FnArg::Captured(arg)) => ResourceParam::from_type(&arg.ty),
+            Some(FnArg::Ignored(ty)) => ResourceParam::from_type(ty),
+            _ => ResourceParam::Into,
+        }
+    }
+
+    /// Return true if the contents of the resource is passed, instead of its path
+    pub(crate) fn reads_contents(self) -> bool {
+        self == ResourceParam::Bytes || self == ResourceParam::Str
+    }
+
+    /// Expression passing the resource to the annotated function; if the contents is passed, the
+    /// resource must be a file, otherwise an error is reported at the pattern.
+    pub(crate) fn arg<T: ToTokens>(
+        self,
+        path: &Path,
+        pattern: &T,
+    ) -> Result<proc_macro2::TokenStream> {
+        Ok(self.convert(self.value(path, pattern)?))
+    }
+
+    /// Static value representing the resource, either the path as `&'static str`, or the
+    /// contents embedded at compile-time; errors are reported at the pattern.
+    pub(crate) fn value<T: ToTokens>(
+        self,
+        path: &Path,
+        pattern: &T,
+    ) -> Result<proc_macro2::TokenStream> {
+        if self.reads_contents() && !path.is_file() {
+            return Err(Error::new_spanned(
+                pattern,
+                format!(
+                    "resource {} is not a file, its contents can not be passed to the function",
+                    path.display()
+                ),
+            ));
+        }
+        let path_as_str = path.display().to_string();
+        Ok(match self {
+            ResourceParam::Bytes => quote! { include_bytes!( #path_as_str ) },
+            ResourceParam::Str => quote! { include_str!( #path_as_str ) },
+            _ => quote! { #path_as_str },
+        })
+    }
+
+    /// Type of the static value
+    pub(crate) fn value_type(self) -> proc_macro2::TokenStream {
+        match self {
+            ResourceParam::Bytes => quote! { &'static [u8] },
+            _ => quote! { &'static str },
+        }
+    }
+
+    /// Expression converting the static value into the type of the parameter
+    pub(crate) fn convert(self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            ResourceParam::Into => quote! { #value .into() },
+            ResourceParam::Path => quote! { std::path::Path::new( #value ) },
+            ResourceParam::PathBuf => quote! { std::path::PathBuf::from( #value ) },
+            ResourceParam::PathBufRef => quote! { &std::path::PathBuf::from( #value ) },
+            ResourceParam::Bytes | ResourceParam::Str => value,
+        }
+    }
+}
+
+/// Return true if the function returns a `Result`, such as `std::io::Result<()>`, which is
+/// returned by the generated test as well
+pub(crate) fn returns_result(func: &ItemFn) -> bool {
+    match func.decl.output {
+        ReturnType::Type(_, ref ty) => type_name(ty).as_deref() == Some("Result"),
+        ReturnType::Default => false,
+    }
+}
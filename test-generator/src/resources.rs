@@ -0,0 +1,384 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Locating the resources matching the patterns of `test_resources` and `bench_resources`
+
+use glob::{glob_with, MatchOptions, Paths, Pattern, PatternError};
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use syn::parse::Result;
+use syn::{Error, Lit, LitStr};
+#[cfg(feature = "gitignore")]
+use test_generator_common::gitignore::IgnoreFiles;
+
+use crate::attributes::lit_str_value;
+use crate::env::expand_variables;
+use crate::metadata::is_sidecar;
+use crate::template::{slash_path, substitute, Captures, PatternTemplate};
+
+/// Return the path as string, or an error pointing at the tokens the path stems from
+pub(crate) fn path_to_str<'p, T: ToTokens>(path: &'p Path, spanned: &T) -> Result<&'p str> {
+    path.to_str().ok_or_else(|| {
+        Error::new_spanned(
+            spanned,
+            format!("bad encoding, path {:?} is not valid UTF-8", path),
+        )
+    })
+}
+
+/// Map the error of an invalid glob pattern onto the pattern literal
+fn pattern_error<T: ToTokens>(err: PatternError, pattern_lit: &T) -> Error {
+    Error::new_spanned(
+        pattern_lit,
+        format!("invalid glob pattern at position {}: {}", err.pos, err.msg),
+    )
+}
+
+/// Expand the glob pattern, reporting errors at the pattern literal
+pub(crate) fn glob_paths(
+    pattern: &str,
+    pattern_lit: &Lit,
+    options: MatchOptions,
+) -> Result<Vec<PathBuf>> {
+    let paths: Paths =
+        glob_with(pattern, options).map_err(|err| pattern_error(err, pattern_lit))?;
+
+    paths
+        .map(|entry| {
+            entry.map_err(|err| {
+                Error::new_spanned(
+                    pattern_lit,
+                    format!("failed to read {}: {}", err.path().display(), err.error()),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Base directory the resource pattern is resolved against
+#[derive(Clone, Copy)]
+pub(crate) enum ResourceRoot {
+    /// Directory containing the `Cargo.toml` of the crate being compiled (default)
+    Manifest,
+    /// Directory containing the `Cargo.toml` of the enclosing workspace
+    Workspace,
+    /// Directory `OUT_DIR` assigned to the build-script of the crate being compiled
+    OutDir,
+}
+
+impl ResourceRoot {
+    /// Map the value of the `root` parameter onto the variant
+    pub(crate) fn from_name(name: &str) -> Option<ResourceRoot> {
+        match name {
+            "manifest" => Some(ResourceRoot::Manifest),
+            "workspace" => Some(ResourceRoot::Workspace),
+            "out_dir" => Some(ResourceRoot::OutDir),
+            _ => None,
+        }
+    }
+
+    /// Absolute directory of this root, as seen by the compiler invoking the macro;
+    /// errors are reported at the given span.
+    pub(crate) fn dir(self, span: Span) -> Result<PathBuf> {
+        match self {
+            ResourceRoot::Manifest => manifest_dir(span),
+            ResourceRoot::Workspace => Ok(workspace_dir(&manifest_dir(span)?)),
+            ResourceRoot::OutDir => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| {
+                    Error::new(
+                        span,
+                        "OUT_DIR is not defined, root = \"out_dir\" requires a build-script `build.rs`",
+                    )
+                }),
+        }
+    }
+}
+
+/// Directory of the crate being compiled; falling back to the working directory if the
+/// compiler has not been invoked by cargo.
+fn manifest_dir(span: Span) -> Result<PathBuf> {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .ok_or_else(|| Error::new(span, "CARGO_MANIFEST_DIR is not defined"))
+}
+
+/// Nearest ancestor of the manifest directory declaring a `[workspace]`; the manifest
+/// directory itself if the crate is not member of a workspace.
+fn workspace_dir(manifest_dir: &Path) -> PathBuf {
+    manifest_dir
+        .ancestors()
+        .find(|dir| is_workspace_manifest(&dir.join("Cargo.toml")))
+        .unwrap_or(manifest_dir)
+        .to_path_buf()
+}
+
+/// Return true if the file is a `Cargo.toml` containing a `[workspace]` section
+fn is_workspace_manifest(path: &Path) -> bool {
+    std::fs::read_to_string(path)
+        .map(|content| content.lines().any(|line| line.trim() == "[workspace]"))
+        .unwrap_or(false)
+}
+
+/// Prefix the pattern with the root directory, unless the pattern is absolute already.
+/// The root directory is escaped, as it might contain glob meta-characters; the pattern is
+/// validated beforehand, so errors refer to the position within the user's pattern.
+pub(crate) fn resolve_pattern(root_dir: &Path, pattern: &str, pattern_lit: &Lit) -> Result<String> {
+    Pattern::new(pattern).map_err(|err| pattern_error(err, pattern_lit))?;
+    if Path::new(pattern).is_absolute() {
+        return Ok(pattern.to_string());
+    }
+    let root_str = path_to_str(root_dir, pattern_lit)?;
+    let resolved = Path::new(&Pattern::escape(root_str)).join(pattern);
+    path_to_str(&resolved, pattern_lit).map(str::to_string)
+}
+
+/// Resource matching one of the include patterns
+pub(crate) struct Resource {
+    /// Absolute path, the generated function is invoked with
+    pub(crate) path: PathBuf,
+    /// Path relative to the root directory, forming the name of the generated function
+    pub(crate) rel_path: PathBuf,
+    /// Index of the first include pattern matching the resource
+    pub(crate) pattern: usize,
+    /// Text captured by the placeholders of the pattern, such as `{case}`
+    pub(crate) captures: Captures,
+}
+
+impl Resource {
+    /// Text substituted into the template of the test-name; the placeholders of the pattern,
+    /// followed by the parts of the relative path: `{path}`, `{parent}` (the name of the
+    /// directory), `{file}`, `{stem}` and `{ext}`. The placeholders of the pattern take precedence.
+    pub(crate) fn name_captures(&self) -> Captures {
+        let part = |part: Option<&std::ffi::OsStr>| {
+            part.map(|part| part.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let mut captures = self.captures.clone();
+        captures.extend(vec![
+            ("path".to_string(), slash_path(&self.rel_path)),
+            (
+                "parent".to_string(),
+                part(self.rel_path.parent().and_then(Path::file_name)),
+            ),
+            ("file".to_string(), part(self.rel_path.file_name())),
+            ("stem".to_string(), part(self.rel_path.file_stem())),
+            ("ext".to_string(), part(self.rel_path.extension())),
+        ]);
+        captures
+    }
+}
+
+/// Pattern selecting resources, such as the exclude patterns; matched against the path relative
+/// to the root directory, or against the absolute path if the pattern is absolute
+pub(crate) struct ResourceFilter {
+    pattern: Pattern,
+    absolute: bool,
+    options: MatchOptions,
+}
+
+impl ResourceFilter {
+    pub(crate) fn parse(lit: &LitStr, options: MatchOptions) -> Result<ResourceFilter> {
+        let value = lit.value();
+        Ok(ResourceFilter {
+            pattern: Pattern::new(&value).map_err(|err| pattern_error(err, lit))?,
+            absolute: Path::new(&value).is_absolute(),
+            options,
+        })
+    }
+
+    /// Parse each of the patterns, reporting errors at the offending literal
+    pub(crate) fn parse_all(lits: &[LitStr], options: MatchOptions) -> Result<Vec<ResourceFilter>> {
+        lits.iter().map(|lit| ResourceFilter::parse(lit, options)).collect()
+    }
+
+    pub(crate) fn matches(&self, resource: &Resource) -> bool {
+        if self.absolute {
+            self.pattern.matches_path_with(&resource.path, self.options)
+        } else {
+            self.pattern.matches_path_with(&resource.rel_path, self.options)
+        }
+    }
+}
+
+/// Expand each include pattern relative to the root directory, and remove all resources
+/// matching any of the exclude patterns. Resources matched by multiple include patterns are
+/// listed once; the resulting list is sorted by path.
+///
+/// An include pattern not matching any resource is reported as error, most likely being a typo.
+/// Environment variables of the patterns are expanded; if a pattern begins with a variable,
+/// such as `${OUT_DIR}`, the paths of its resources are formed relative to the variable instead.
+/// The options control the matching of both the include and the exclude patterns. If
+/// `respect_gitignore`, paths ignored by `.gitignore` and `.ignore` files are skipped.
+pub(crate) fn collect_resources(
+    root_dir: &Path,
+    patterns: &[Lit],
+    exclude: &[LitStr],
+    options: MatchOptions,
+    respect_gitignore: bool,
+) -> Result<Vec<Resource>> {
+    let excludes = ResourceFilter::parse_all(exclude, options)?;
+    let mut is_ignored = ignored_filter(respect_gitignore);
+
+    let mut resources = BTreeMap::new();
+    for (index, pattern_lit) in patterns.iter().enumerate() {
+        let verbatim = lit_str_value(pattern_lit)?;
+        let (pattern, var_dir) = expand_variables(&verbatim, pattern_lit)?;
+        let base_dir = var_dir.as_deref().unwrap_or(root_dir);
+        let template = PatternTemplate::parse(&pattern, pattern_lit, options.case_sensitive)?;
+        let mut paths = glob_paths(
+            &resolve_pattern(root_dir, template.glob(), pattern_lit)?,
+            pattern_lit,
+            options,
+        )?;
+        // sidecar files control the tests of the resources, without being tested themselves
+        paths.retain(|path| !is_sidecar(path));
+
+        if paths.is_empty() {
+            // relative patterns are searched in the root directory, absolute ones in the
+            // directory of a leading variable, or as expanded
+            let searched = match var_dir {
+                Some(ref dir) => format!(" in directory {}", dir.display()),
+                None if !Path::new(&pattern).is_absolute() => {
+                    format!(" in directory {}", root_dir.display())
+                }
+                None if pattern != verbatim => format!(", expanded to {:?}", template.glob()),
+                None => String::new(),
+            };
+            return Err(Error::new_spanned(
+                pattern_lit,
+                format!("no resource matching the pattern {:?}{}", verbatim, searched),
+            ));
+        }
+
+        let matched = paths.len();
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| !is_ignored(path))
+            .collect();
+        if paths.is_empty() {
+            return Err(Error::new_spanned(
+                pattern_lit,
+                format!(
+                    "all {} resources matching the pattern {:?} are ignored by .gitignore files",
+                    matched, verbatim
+                ),
+            ));
+        }
+
+        for path in paths {
+            // validate encoding once, all names and paths are derived from it
+            path_to_str(&path, pattern_lit)?;
+            let rel_path = path.strip_prefix(base_dir).unwrap_or(&path).to_path_buf();
+            let captures = if Path::new(&pattern).is_absolute() {
+                template.captures(&path)
+            } else {
+                template.captures(&rel_path)
+            };
+            resources.entry(path.clone()).or_insert(Resource {
+                path,
+                rel_path,
+                pattern: index,
+                captures,
+            });
+        }
+    }
+
+    let total = resources.len();
+    let resources: Vec<Resource> = resources
+        .into_values()
+        .filter(|resource| !excludes.iter().any(|exclude| exclude.matches(resource)))
+        .collect();
+
+    if resources.is_empty() {
+        return Err(Error::new_spanned(
+            &exclude[0],
+            format!("all {} resources matching the patterns are excluded", total),
+        ));
+    }
+    Ok(resources)
+}
+
+/// Predicate of the paths ignored by `.gitignore` and `.ignore` files, if respected
+#[cfg(feature = "gitignore")]
+fn ignored_filter(respect_gitignore: bool) -> impl FnMut(&Path) -> bool {
+    let mut ignore_files = IgnoreFiles::default();
+    move |path| respect_gitignore && ignore_files.is_ignored(path)
+}
+
+/// Predicate of the paths ignored by `.gitignore` files, none without the feature `gitignore`,
+/// as the parameter `respect_gitignore` is rejected then
+#[cfg(not(feature = "gitignore"))]
+fn ignored_filter(_respect_gitignore: bool) -> impl FnMut(&Path) -> bool {
+    |_| false
+}
+
+/// Path of the partner resource, substituting the placeholders of the template by the text
+/// captured from the resource; relative templates are resolved against the root directory.
+pub(crate) fn partner_path(
+    root_dir: &Path,
+    resource: &Resource,
+    template: &LitStr,
+) -> Result<PathBuf> {
+    let partner = root_dir.join(substitute(&template.value(), &resource.captures, template)?);
+    path_to_str(&partner, template)?;
+    Ok(partner)
+}
+
+/// Path of the partner resource, which must exist; a missing partner is reported at the
+/// template, naming the resource it belongs to.
+pub(crate) fn existing_partner_path(
+    root_dir: &Path,
+    resource: &Resource,
+    template: &LitStr,
+) -> Result<PathBuf> {
+    let partner = partner_path(root_dir, resource, template)?;
+    if !partner.exists() {
+        return Err(Error::new_spanned(
+            template,
+            format!(
+                "partner resource {} of {} does not exist",
+                partner.display(),
+                resource.rel_path.display()
+            ),
+        ));
+    }
+    Ok(partner)
+}
+
+/// Register the resources as dependencies of the compilation unit, so the macro is expanded
+/// again if any of them is modified. Stable Rust offers no API for this purpose; a hidden
+/// constant embedding each file by `include_bytes!` makes the compiler record the files in
+/// its dep-info. Being an unnamed constant, the contents is not placed in the binary.
+#[cfg(not(feature = "nightly"))]
+pub(crate) fn track_resources(paths: &[&Path]) -> proc_macro2::TokenStream {
+    let files = paths
+        .iter()
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string());
+
+    quote::quote! {
+        #[doc(hidden)]
+        const _: &[&[u8]] = &[ #( include_bytes!( #files ) ),* ];
+    }
+}
+
+/// Register the resources as dependencies of the compilation unit, so the macro is expanded
+/// again if any of them is modified, using the unstable API `proc_macro::tracked`. The parent
+/// directories are registered as well, detecting resources being added next to the existing ones.
+#[cfg(feature = "nightly")]
+pub(crate) fn track_resources(paths: &[&Path]) -> proc_macro2::TokenStream {
+    let mut tracked = std::collections::BTreeSet::new();
+    for path in paths {
+        tracked.insert(*path);
+        tracked.extend(path.parent());
+    }
+    for path in tracked {
+        proc_macro::tracked::path(path.display().to_string());
+    }
+    proc_macro2::TokenStream::new()
+}
@@ -0,0 +1,165 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Sections of a resource file, the attribute `test_sections` generating a test for each
+
+use proc_macro2::Ident;
+use quote::quote;
+use regex::Regex;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, ItemFn, LitStr, Token};
+
+use crate::cases::Case;
+use crate::naming::fn_ident_from_string;
+use crate::resources::{path_to_str, ResourceRoot};
+
+/// Separator line of the sections, if the parameter `separator` is not given
+const DEFAULT_SEPARATOR: &str = r"^--- (\w+)";
+
+/// Section of the file, the text between its separator line and the next one
+struct Section {
+    name: String,
+    /// Line number of the separator line, starting at 1
+    line: usize,
+    text: String,
+}
+
+/// Split the text into sections; each line matching the separator starts a section, named by
+/// the capture group `name`, or otherwise the first capture group. Text preceding the first
+/// separator line is skipped, for example a header comment.
+fn split_sections(text: &str, separator: &Regex) -> std::result::Result<Vec<Section>, String> {
+    let mut sections: Vec<Section> = Vec::new();
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches('\n').trim_end_matches('\r');
+        if let Some(captures) = separator.captures(content) {
+            let name = captures
+                .name("name")
+                .or_else(|| captures.get(1))
+                .map(|name| name.as_str().to_string())
+                .ok_or_else(|| {
+                    format!("line {}: the separator captured no section name", index + 1)
+                })?;
+            if let Some(other) = sections.iter().find(|section| section.name == name) {
+                return Err(format!(
+                    "line {}: section '{}' is defined twice, first at line {}",
+                    index + 1,
+                    name,
+                    other.line
+                ));
+            }
+            sections.push(Section {
+                name,
+                line: index + 1,
+                text: String::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            section.text.push_str(line);
+        }
+    }
+    Ok(sections)
+}
+
+/// Parameters of the attribute `test_sections`, the file and the optional separator
+pub(crate) struct SectionAttributes {
+    /// File of sections, relative to the directory of the crate's `Cargo.toml`
+    path: LitStr,
+    /// Regular expression matching the separator lines, capturing the name of the section
+    separator: Option<LitStr>,
+}
+
+impl SectionAttributes {
+    /// Absolute path of the file
+    pub(crate) fn file_path(&self) -> Result<PathBuf> {
+        let root_dir = ResourceRoot::Manifest.dir(self.path.span())?;
+        let path = root_dir.join(self.path.value());
+        path_to_str(&path, &self.path)?;
+        Ok(path)
+    }
+
+    /// Regular expression matching the separator lines
+    fn separator(&self) -> Result<Regex> {
+        match self.separator {
+            Some(ref separator) => {
+                let regex = Regex::new(&separator.value())
+                    .map_err(|err| Error::new_spanned(separator, err.to_string()))?;
+                if regex.captures_len() < 2 {
+                    return Err(Error::new_spanned(
+                        separator,
+                        "the separator must capture the name of the section, such as \"^--- (\\\\w+)\"",
+                    ));
+                }
+                Ok(regex)
+            }
+            None => Ok(Regex::new(DEFAULT_SEPARATOR).expect("valid default separator")),
+        }
+    }
+
+    /// A case for each section of the file, passing the text of the section, and its name if
+    /// the function takes a second parameter; the test-name is formed from the section name.
+    pub(crate) fn cases(&self, func: &ItemFn) -> Result<Vec<Case>> {
+        let path = self.file_path()?;
+        let error = |msg: String| {
+            Error::new_spanned(&self.path, format!("{}: {}", path.display(), msg))
+        };
+        let text = std::fs::read_to_string(&path).map_err(|err| error(err.to_string()))?;
+        let sections = split_sections(&text, &self.separator()?).map_err(error)?;
+        if sections.is_empty() {
+            return Err(error("no line matches the separator".into()));
+        }
+
+        let func_ident = &func.ident;
+        let with_name = func.decl.inputs.len() > 1;
+        Ok(sections
+            .iter()
+            .map(|section| {
+                let text = &section.text;
+                let name = &section.name;
+                let args = if with_name {
+                    quote! { #text .into(), #name .into() }
+                } else {
+                    quote! { #text .into() }
+                };
+                Case {
+                    ident: fn_ident_from_string(func_ident, name),
+                    origin: name.clone(),
+                    args,
+                    context: format!(
+                        "function `{}` failed on section {} of {}, line {}",
+                        func_ident,
+                        name,
+                        self.path.value(),
+                        section.line
+                    ),
+                }
+            })
+            .collect())
+    }
+}
+
+/// SectionAttributes parser, the file followed by the optional parameter `separator`
+impl Parse for SectionAttributes {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path = input.parse::<LitStr>()?;
+        let mut separator = None;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match name.to_string().as_str() {
+                "separator" => separator = Some(input.parse::<LitStr>()?),
+                other => {
+                    return Err(Error::new(
+                        name.span(),
+                        format!("unknown parameter '{}', expected 'separator'", other),
+                    ))
+                }
+            }
+        }
+        Ok(SectionAttributes { path, separator })
+    }
+}
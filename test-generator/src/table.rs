@@ -0,0 +1,608 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Table files of the attribute `test_cases`, each row forming the arguments of a test
+
+use proc_macro2::{Ident, Literal, Span};
+use quote::quote;
+use std::path::{Path, PathBuf};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, FnArg, ItemFn, LitStr, Pat, Token, Type};
+
+use crate::cases::Case;
+use crate::naming::fn_ident_from_string;
+use crate::params::type_name;
+use crate::resources::{path_to_str, ResourceRoot};
+
+/// Value of a field, a string, number, boolean or list thereof; unquoted CSV fields are kept as
+/// text, their type being derived from the parameter
+#[derive(Clone, Debug, PartialEq)]
+enum Field {
+    Str(String),
+    Unquoted(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    List(Vec<Field>),
+}
+
+/// Type of a parameter, determining the literal a field is passed as
+enum ParamKind {
+    /// `&str` or `String`, receiving the text of any scalar field
+    Text,
+    /// Integer types, such as `i64` or `usize`
+    Int,
+    /// `f32` or `f64`, the type naming the constants `NAN` and `INFINITY`
+    Float(Ident),
+    /// Any other type, receiving the literal of the field's own type
+    Other,
+}
+
+impl ParamKind {
+    /// Derive the kind from the type of the parameter
+    fn from_type(ty: &Type) -> ParamKind {
+        let name = match ty {
+            Type::Reference(reference) => match type_name(&reference.elem).as_deref() {
+                Some("str") => return ParamKind::Text,
+                _ => return ParamKind::Other,
+            },
+            ty => type_name(ty),
+        };
+        match name.as_deref() {
+            Some("String") => ParamKind::Text,
+            Some("i8") | Some("i16") | Some("i32") | Some("i64") | Some("i128") | Some("isize")
+            | Some("u8") | Some("u16") | Some("u32") | Some("u64") | Some("u128")
+            | Some("usize") => ParamKind::Int,
+            Some(float @ "f32") | Some(float @ "f64") => {
+                ParamKind::Float(Ident::new(float, Span::call_site()))
+            }
+            _ => ParamKind::Other,
+        }
+    }
+}
+
+impl Field {
+    /// Text of the field, forming the test-name if the field is the key
+    fn text(&self) -> String {
+        match self {
+            Field::Str(text) | Field::Unquoted(text) => text.clone(),
+            Field::Int(value) => value.to_string(),
+            Field::Float(value) => value.to_string(),
+            Field::Bool(value) => value.to_string(),
+            Field::List(fields) => {
+                let texts: Vec<String> = fields.iter().map(Field::text).collect();
+                texts.join("_")
+            }
+        }
+    }
+
+    /// Literal expression of the field passed to a parameter of the kind; strings are
+    /// converted by `into()` into the type of the parameter, such as `&str` or `String`
+    fn literal(&self, kind: &ParamKind) -> std::result::Result<proc_macro2::TokenStream, String> {
+        match (kind, self) {
+            (_, Field::List(_)) | (ParamKind::Other, _) => self.guessed().typed_literal(true),
+            (ParamKind::Text, field) => {
+                let text = field.text();
+                Ok(quote! { #text .into() })
+            }
+            (ParamKind::Int, Field::Int(_)) => self.typed_literal(false),
+            (ParamKind::Int, Field::Unquoted(text)) => match text.parse::<i64>() {
+                Ok(value) => Field::Int(value).typed_literal(false),
+                Err(_) => Err(format!("expected an integer, found '{}'", text)),
+            },
+            (ParamKind::Float(ty), Field::Int(value)) => float_literal(ty, *value as f64),
+            (ParamKind::Float(ty), Field::Float(value)) => float_literal(ty, *value),
+            (ParamKind::Float(ty), Field::Unquoted(text)) => match text.parse::<f64>() {
+                Ok(value) => float_literal(ty, value),
+                Err(_) => Err(format!("expected a number, found '{}'", text)),
+            },
+            (ParamKind::Int, field) => {
+                Err(format!("expected an integer, found {}", field.describe()))
+            }
+            (_, field) => Err(format!("expected a number, found {}", field.describe())),
+        }
+    }
+
+    /// The field, an unquoted CSV field being a number or boolean if parsing as such
+    fn guessed(&self) -> Field {
+        let text = match self {
+            Field::Unquoted(text) => text,
+            Field::List(fields) => return Field::List(fields.iter().map(Field::guessed).collect()),
+            field => return field.clone(),
+        };
+        if let Ok(value) = text.parse::<i64>() {
+            Field::Int(value)
+        } else if let Ok(value) = text.parse::<f64>() {
+            Field::Float(value)
+        } else if let Ok(value) = text.parse::<bool>() {
+            Field::Bool(value)
+        } else {
+            Field::Str(text.clone())
+        }
+    }
+
+    /// Description of the field in error messages
+    fn describe(&self) -> String {
+        match self {
+            Field::Str(text) => format!("the string {:?}", text),
+            Field::Bool(value) => format!("the boolean {}", value),
+            field => format!("'{}'", field.text()),
+        }
+    }
+
+    /// Literal expression of the field's own type; strings are converted by `into()`, unless
+    /// being element of a list
+    fn typed_literal(
+        &self,
+        convert: bool,
+    ) -> std::result::Result<proc_macro2::TokenStream, String> {
+        Ok(match self {
+            Field::Str(text) | Field::Unquoted(text) if convert => quote! { #text .into() },
+            Field::Str(text) | Field::Unquoted(text) => quote! { #text },
+            Field::Int(value) if *value < 0 => {
+                let lit = Literal::u64_unsuffixed(value.unsigned_abs());
+                quote! { - #lit }
+            }
+            Field::Int(value) => {
+                let lit = Literal::i64_unsuffixed(*value);
+                quote! { #lit }
+            }
+            Field::Float(value) if !value.is_finite() => {
+                return Err(format!("float {} has no literal", value))
+            }
+            Field::Float(value) if *value < 0.0 => {
+                let lit = Literal::f64_unsuffixed(-value);
+                quote! { - #lit }
+            }
+            Field::Float(value) => {
+                let lit = Literal::f64_unsuffixed(*value);
+                quote! { #lit }
+            }
+            Field::Bool(value) => quote! { #value },
+            Field::List(fields) => {
+                let items = fields
+                    .iter()
+                    .map(|field| field.typed_literal(false))
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+                quote! { [ #( #items ),* ] }
+            }
+        })
+    }
+}
+
+/// Literal of the float type, the constants of the type if not finite
+fn float_literal(ty: &Ident, value: f64) -> std::result::Result<proc_macro2::TokenStream, String> {
+    Ok(match value {
+        value if value.is_nan() => quote! { #ty::NAN },
+        value if value == f64::INFINITY => quote! { #ty::INFINITY },
+        value if value == f64::NEG_INFINITY => quote! { #ty::NEG_INFINITY },
+        value => Field::Float(value).typed_literal(false)?,
+    })
+}
+
+/// Row of the table, the fields by column name
+type Row = Vec<(String, Field)>;
+
+/// Parse the CSV text; the first line names the columns.
+fn parse_csv(text: &str) -> std::result::Result<Vec<Row>, String> {
+    let mut records: Vec<Vec<Field>> = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    let mut at_field_start = true;
+    loop {
+        let c = chars.next();
+        match c {
+            Some('"') if at_field_start => {
+                quoted = true;
+                at_field_start = false;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => return Err("unterminated quoted field".to_string()),
+                    }
+                }
+            }
+            Some(',') => {
+                record.push(csv_field(std::mem::take(&mut field), quoted));
+                quoted = false;
+                at_field_start = true;
+            }
+            Some('\r') if chars.peek() == Some(&'\n') => {}
+            Some('\n') | None => {
+                let empty_line = record.is_empty() && field.is_empty() && !quoted;
+                if !empty_line {
+                    record.push(csv_field(std::mem::take(&mut field), quoted));
+                    records.push(std::mem::take(&mut record));
+                }
+                quoted = false;
+                at_field_start = true;
+                if c.is_none() {
+                    break;
+                }
+            }
+            Some(c) if quoted => {
+                return Err(format!("unexpected '{}' following a quoted field", c));
+            }
+            Some(c) => {
+                field.push(c);
+                at_field_start = false;
+            }
+        }
+    }
+
+    let mut records = records.into_iter();
+    let columns: Vec<String> = match records.next() {
+        Some(header) => header.iter().map(Field::text).collect(),
+        None => return Err("missing header line naming the columns".to_string()),
+    };
+    records
+        .enumerate()
+        .map(|(index, record)| {
+            if record.len() != columns.len() {
+                return Err(format!(
+                    "row {} has {} fields, but the header names {} columns",
+                    index + 1,
+                    record.len(),
+                    columns.len()
+                ));
+            }
+            Ok(columns.iter().cloned().zip(record).collect())
+        })
+        .collect()
+}
+
+/// Field of a CSV record; quoted fields are strings, the type of unquoted fields is derived
+/// from the parameter
+fn csv_field(text: String, quoted: bool) -> Field {
+    match quoted {
+        true => Field::Str(text),
+        false => Field::Unquoted(text),
+    }
+}
+
+/// Field of a JSON value
+fn json_field(value: serde_json::Value) -> std::result::Result<Field, String> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::String(text) => Field::Str(text),
+        Value::Bool(value) => Field::Bool(value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => Field::Int(value),
+            None => Field::Float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::Array(values) => Field::List(
+            values
+                .into_iter()
+                .map(json_field)
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        Value::Null => return Err("null has no literal".to_string()),
+        Value::Object(_) => return Err("object has no literal".to_string()),
+    })
+}
+
+/// Parse the JSON text, an array of objects
+fn parse_json(text: &str) -> std::result::Result<Vec<Row>, String> {
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_str(text).map_err(|err| err.to_string())?;
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(column, value)| Ok((column, json_field(value)?)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Field of a TOML value
+fn toml_field(value: toml::Value) -> std::result::Result<Field, String> {
+    use toml::Value;
+    Ok(match value {
+        Value::String(text) => Field::Str(text),
+        Value::Integer(value) => Field::Int(value),
+        Value::Float(value) => Field::Float(value),
+        Value::Boolean(value) => Field::Bool(value),
+        Value::Datetime(datetime) => Field::Str(datetime.to_string()),
+        Value::Array(values) => Field::List(
+            values
+                .into_iter()
+                .map(toml_field)
+                .collect::<std::result::Result<_, _>>()?,
+        ),
+        Value::Table(_) => return Err("table has no literal".to_string()),
+    })
+}
+
+/// Parse the TOML text, a single array of tables such as `[[case]]`
+fn parse_toml(text: &str) -> std::result::Result<Vec<Row>, String> {
+    let table: toml::value::Table = toml::from_str(text).map_err(|err| err.to_string())?;
+    let mut arrays = table.into_iter().filter_map(|(name, value)| match value {
+        toml::Value::Array(rows) => Some((name, rows)),
+        _ => None,
+    });
+    let rows = match (arrays.next(), arrays.next()) {
+        (Some((_, rows)), None) => rows,
+        (Some((first, _)), Some((second, _))) => {
+            return Err(format!(
+                "expected a single array of tables, found [[{}]] and [[{}]]",
+                first, second
+            ))
+        }
+        (None, _) => return Err("expected an array of tables, such as [[case]]".to_string()),
+    };
+    rows.into_iter()
+        .map(|row| match row {
+            toml::Value::Table(row) => row
+                .into_iter()
+                .map(|(column, value)| Ok((column, toml_field(value)?)))
+                .collect(),
+            _ => Err("expected an array of tables, such as [[case]]".to_string()),
+        })
+        .collect()
+}
+
+/// Names and kinds of the parameters of the function, each name being the name of a column
+fn params(func: &ItemFn) -> Result<Vec<(Ident, ParamKind)>> {
+    func.decl
+        .inputs
+        .iter()
+        .map(|input| match input {
+            FnArg::Captured(arg) => match arg.pat {
+                Pat::Ident(ref pat) => Ok((pat.ident.clone(), ParamKind::from_type(&arg.ty))),
+                ref pat => Err(Error::new_spanned(pat, "expected the name of a column")),
+            },
+            input => Err(Error::new_spanned(input, "expected the name of a column")),
+        })
+        .collect()
+}
+
+/// Parameters of the attribute `test_cases`, the table file and the optional key column
+pub(crate) struct TableAttributes {
+    /// Table file, relative to the directory of the crate's `Cargo.toml`
+    path: LitStr,
+    /// Column forming the test-names, by default the first one of each row
+    key: Option<LitStr>,
+}
+
+impl TableAttributes {
+    /// Absolute path of the table file
+    pub(crate) fn table_path(&self) -> Result<PathBuf> {
+        let root_dir = ResourceRoot::Manifest.dir(self.path.span())?;
+        let path = root_dir.join(self.path.value());
+        path_to_str(&path, &self.path)?;
+        Ok(path)
+    }
+
+    /// Read the rows of the table, the format is derived from the extension of the file
+    fn read_rows(&self, path: &Path) -> Result<Vec<Row>> {
+        let error =
+            |msg: String| Error::new_spanned(&self.path, format!("{}: {}", path.display(), msg));
+        let parse = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => parse_csv,
+            Some("json") => parse_json,
+            Some("toml") => parse_toml,
+            _ => {
+                return Err(error(
+                    "unknown format, expected extension csv, json or toml".into(),
+                ))
+            }
+        };
+        let text = std::fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+        let rows = parse(&text).map_err(error)?;
+        if rows.is_empty() {
+            return Err(error("the table has no rows".into()));
+        }
+        Ok(rows)
+    }
+
+    /// A case for each row of the table, passing the field of the column named like each
+    /// parameter; the test-name is formed from the function name and the key field.
+    pub(crate) fn cases(&self, func: &ItemFn) -> Result<Vec<Case>> {
+        let path = self.table_path()?;
+        let rows = self.read_rows(&path)?;
+        let params = params(func)?;
+        let func_ident = &func.ident;
+
+        rows.iter()
+            .enumerate()
+            .map(|(index, row)| {
+                let error = |msg: String| {
+                    Error::new_spanned(
+                        &self.path,
+                        format!("{}: row {}: {}", path.display(), index + 1, msg),
+                    )
+                };
+                let field = |column: &str| {
+                    row.iter()
+                        .find(|(name, _)| name == column)
+                        .map(|(_, field)| field)
+                        .ok_or_else(|| error(format!("missing column '{}'", column)))
+                };
+                let key = match self.key {
+                    Some(ref key) => field(&key.value())?,
+                    None => match row.first() {
+                        Some((_, field)) => field,
+                        None => return Err(error("the row has no columns".into())),
+                    },
+                };
+                let key_text = key.text();
+                let args = params
+                    .iter()
+                    .map(|(param, kind)| {
+                        field(&param.to_string())?
+                            .literal(kind)
+                            .map_err(|msg| error(format!("column '{}': {}", param, msg)))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Case {
+                    ident: fn_ident_from_string(func_ident, &key_text),
+                    origin: format!("row {}", index + 1),
+                    args: quote! { #( #args ),* },
+                    context: format!(
+                        "function `{}` failed on case {} of {}",
+                        func_ident,
+                        key_text,
+                        self.path.value()
+                    ),
+                })
+            })
+            .collect()
+    }
+}
+
+/// TableAttributes parser, the table file followed by the optional parameter `key`
+impl Parse for TableAttributes {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path = input.parse::<LitStr>()?;
+        let mut key = None;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match name.to_string().as_str() {
+                "key" => key = Some(input.parse::<LitStr>()?),
+                other => {
+                    return Err(Error::new(
+                        name.span(),
+                        format!("unknown parameter '{}', expected 'key'", other),
+                    ))
+                }
+            }
+        }
+        Ok(TableAttributes { path, key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(fields: &[(&str, Field)]) -> Row {
+        fields
+            .iter()
+            .map(|(column, field)| (column.to_string(), field.clone()))
+            .collect()
+    }
+
+    fn literal(field: Field, ty: &str) -> String {
+        let kind = ParamKind::from_type(&syn::parse_str(ty).unwrap());
+        field.literal(&kind).unwrap().to_string()
+    }
+
+    #[test]
+    fn csv_quoted_fields_are_strings() {
+        let rows = parse_csv("name,input\nzero,\"0\"\n\"a, b\",7\n").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                row(&[
+                    ("name", Field::Unquoted("zero".into())),
+                    ("input", Field::Str("0".into()))
+                ]),
+                row(&[
+                    ("name", Field::Str("a, b".into())),
+                    ("input", Field::Unquoted("7".into()))
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_doubled_quotes_are_escapes() {
+        let rows = parse_csv("text\n\"say \"\"hi\"\"\"\n\"\"\n").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                row(&[("text", Field::Str("say \"hi\"".into()))]),
+                row(&[("text", Field::Str("".into()))]),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_crlf_and_empty_lines() {
+        let rows = parse_csv("a,b\r\n\r\n1,2\r\n\n3,4").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                row(&[
+                    ("a", Field::Unquoted("1".into())),
+                    ("b", Field::Unquoted("2".into()))
+                ]),
+                row(&[
+                    ("a", Field::Unquoted("3".into())),
+                    ("b", Field::Unquoted("4".into()))
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_errors() {
+        assert_eq!(
+            parse_csv("a,b\n1,2\n3\n"),
+            Err("row 2 has 1 fields, but the header names 2 columns".to_string())
+        );
+        assert_eq!(
+            parse_csv("a\n\"1"),
+            Err("unterminated quoted field".to_string())
+        );
+        assert_eq!(
+            parse_csv("a\n\"1\"x\n"),
+            Err("unexpected 'x' following a quoted field".to_string())
+        );
+        assert_eq!(
+            parse_csv(""),
+            Err("missing header line naming the columns".to_string())
+        );
+    }
+
+    #[test]
+    fn literal_derived_from_parameter_type() {
+        let cases = vec![
+            (Field::Unquoted("42".into()), "&str", quote! { "42".into() }),
+            (
+                Field::Unquoted("nan".into()),
+                "String",
+                quote! { "nan".into() },
+            ),
+            (Field::Int(7), "&'static str", quote! { "7".into() }),
+            (Field::Unquoted("-42".into()), "i64", quote! { -42 }),
+            (Field::Unquoted("1".into()), "f64", quote! { 1.0 }),
+            (
+                Field::Unquoted("inf".into()),
+                "f32",
+                quote! { f32::INFINITY },
+            ),
+            (Field::Unquoted("true".into()), "bool", quote! { true }),
+            (Field::Unquoted("x".into()), "Name", quote! { "x".into() }),
+        ];
+        for (field, ty, expected) in cases {
+            assert_eq!(literal(field, ty), expected.to_string(), "type {}", ty);
+        }
+    }
+
+    #[test]
+    fn literal_rejects_non_numbers() {
+        let kind = ParamKind::from_type(&syn::parse_str("u32").unwrap());
+        assert_eq!(
+            Field::Unquoted("4.5".into()).literal(&kind).map(|_| ()),
+            Err("expected an integer, found '4.5'".to_string())
+        );
+        assert_eq!(
+            Field::Str("1".into()).literal(&kind).map(|_| ()),
+            Err("expected an integer, found the string \"1\"".to_string())
+        );
+    }
+}
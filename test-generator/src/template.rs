@@ -0,0 +1,232 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Resource patterns with named placeholders, such as `res/{case}/input.txt`
+
+use quote::ToTokens;
+use regex::Regex;
+use std::path::{Component, Path};
+use syn::parse::Result;
+use syn::Error;
+
+/// Placeholder name and the text it captured from a resource path
+pub(crate) type Captures = Vec<(String, String)>;
+
+/// Resource pattern, in which each placeholder `{name}` matches a non-empty part of a path
+/// component, like the wildcard `*` does. The captured text may be substituted into other
+/// templates, for example forming the path of the partner resource `res/{case}/expect.txt`.
+pub(crate) struct PatternTemplate {
+    /// Glob pattern, each placeholder replaced by `*`
+    glob: String,
+    /// Regular expression capturing the placeholders, if any
+    regex: Option<Regex>,
+}
+
+/// Split the template into literal text and placeholder names; `{{` and `}}` denote literal
+/// braces. Errors are reported at the tokens of the template.
+fn split_template<T: ToTokens>(template: &str, spanned: &T) -> Result<Vec<(bool, String)>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let valid = !name.is_empty()
+                    && !name.starts_with(|c: char| c.is_ascii_digit())
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid {
+                    return Err(Error::new_spanned(
+                        spanned,
+                        format!(
+                            "invalid placeholder {{{}}}, expected a name such as {{case}}",
+                            name
+                        ),
+                    ));
+                }
+                parts.push((false, std::mem::take(&mut text)));
+                parts.push((true, name));
+            }
+            '}' => {
+                return Err(Error::new_spanned(
+                    spanned,
+                    "unmatched '}', literal braces are written as '{{' and '}}'",
+                ))
+            }
+            c => text.push(c),
+        }
+    }
+    parts.push((false, text));
+    Ok(parts)
+}
+
+/// Translate a glob pattern into the equivalent regular expression, without anchors
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' || c == '^' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+/// Return the path with '/' as separator, as used within patterns on all platforms
+pub(crate) fn slash_path(path: &Path) -> String {
+    let mut slashed = String::new();
+    for component in path.components() {
+        match component {
+            Component::RootDir => {}
+            component => slashed.push_str(&component.as_os_str().to_string_lossy()),
+        }
+        if !slashed.ends_with('/') {
+            slashed.push('/');
+        }
+    }
+    slashed.pop();
+    slashed
+}
+
+impl PatternTemplate {
+    /// Parse the pattern, errors are reported at the given tokens; the placeholders capture
+    /// text of paths matched case-insensitively as well, unless `case_sensitive`
+    pub(crate) fn parse<T: ToTokens>(
+        pattern: &str,
+        spanned: &T,
+        case_sensitive: bool,
+    ) -> Result<PatternTemplate> {
+        let parts = split_template(pattern, spanned)?;
+
+        let mut glob = String::new();
+        let mut regex = String::from(if case_sensitive { "^" } else { "(?i)^" });
+        let mut names: Vec<&str> = Vec::new();
+        for (is_placeholder, part) in &parts {
+            if *is_placeholder {
+                if names.contains(&part.as_str()) {
+                    return Err(Error::new_spanned(
+                        spanned,
+                        format!("placeholder {{{}}} is used twice", part),
+                    ));
+                }
+                names.push(part);
+                glob.push('*');
+                regex.push_str(&format!("(?P<{}>[^/]+)", part));
+            } else {
+                glob.push_str(part);
+                regex.push_str(&glob_to_regex(part));
+            }
+        }
+        regex.push('$');
+
+        let regex = if names.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&regex).map_err(|err| Error::new_spanned(spanned, err.to_string()))?)
+        };
+        Ok(PatternTemplate { glob, regex })
+    }
+
+    /// Glob pattern, each placeholder replaced by the wildcard `*`
+    pub(crate) fn glob(&self) -> &str {
+        &self.glob
+    }
+
+    /// Return the text captured by each placeholder from the path, which must be relative
+    /// if the pattern is relative, and absolute otherwise
+    pub(crate) fn captures(&self, path: &Path) -> Captures {
+        let regex = match self.regex {
+            Some(ref regex) => regex,
+            None => return Captures::new(),
+        };
+        let path = slash_path(path);
+        let captures = match regex.captures(&path) {
+            Some(captures) => captures,
+            None => return Captures::new(),
+        };
+        regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| {
+                captures
+                    .name(name)
+                    .map(|value| (name.to_string(), value.as_str().to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Substitute the placeholders of the template by the captured text; errors, such as unknown
+/// placeholders, are reported at the given tokens
+pub(crate) fn substitute<T: ToTokens>(
+    template: &str,
+    captures: &Captures,
+    spanned: &T,
+) -> Result<String> {
+    let mut substituted = String::new();
+    for (is_placeholder, part) in split_template(template, spanned)? {
+        if !is_placeholder {
+            substituted.push_str(&part);
+            continue;
+        }
+        match captures.iter().find(|(name, _)| *name == part) {
+            Some((_, value)) => substituted.push_str(value),
+            None => {
+                let known: Vec<String> = captures
+                    .iter()
+                    .map(|(name, _)| format!("{{{}}}", name))
+                    .collect();
+                return Err(Error::new_spanned(
+                    spanned,
+                    format!(
+                        "unknown placeholder {{{}}}, {}",
+                        part,
+                        if known.is_empty() {
+                            "the pattern defines no placeholders".to_string()
+                        } else {
+                            format!("expected one of {}", known.join(", "))
+                        }
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(substituted)
+}
@@ -0,0 +1,88 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Parameters of the attributes `test_values` and `bench_values`
+
+use quote::quote;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{Expr, Ident, LitStr, Token};
+
+use crate::cases::Case;
+use crate::naming::{expr_name, fn_ident_from_string};
+
+/// Value the annotated function is invoked with, optionally named by `name => expr`
+pub(crate) struct Value {
+    name: Option<String>,
+    expr: Expr,
+}
+
+/// Value parser, either an expression, or an identifier or string literal followed by `=>`
+/// and the expression
+impl Parse for Value {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = if input.peek(Ident) && input.peek2(Token![=>]) {
+            Some(input.parse::<Ident>()?.to_string())
+        } else if input.peek(LitStr) && input.peek2(Token![=>]) {
+            Some(input.parse::<LitStr>()?.value())
+        } else {
+            None
+        };
+        if name.is_some() {
+            input.parse::<Token![=>]>()?;
+        }
+        Ok(Value {
+            name,
+            expr: input.parse()?,
+        })
+    }
+}
+
+/// Values of the attributes `test_values` and `bench_values`, at least one
+pub(crate) struct ValueList {
+    values: Vec<Value>,
+}
+
+impl ValueList {
+    /// A case for each value; the function name is suffixed by the name of the value, or
+    /// otherwise by the stringified expression
+    pub(crate) fn cases(&self, func_ident: &Ident) -> Result<Vec<Case>> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let expr = &value.expr;
+                let stringified = quote!(#expr).to_string();
+                let context = format!("function `{}` failed on value {}", func_ident, stringified);
+                Ok(match value.name {
+                    Some(ref name) => Case {
+                        ident: fn_ident_from_string(func_ident, name),
+                        origin: name.clone(),
+                        args: quote! { #expr },
+                        context: format!("{} named {:?}", context, name),
+                    },
+                    None => Case {
+                        ident: fn_ident_from_string(func_ident, &expr_name(expr, index)),
+                        origin: stringified,
+                        args: quote! { #expr },
+                        context,
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// ValueList parser, a comma separated list of values
+impl Parse for ValueList {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Err(input.error("expected at least one value, such as `10, 100, 1000`"));
+        }
+        let values = Punctuated::<Value, Token![,]>::parse_terminated(input)?;
+        Ok(ValueList {
+            values: values.into_iter().collect(),
+        })
+    }
+}
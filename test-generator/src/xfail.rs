@@ -0,0 +1,97 @@
+// Copyright (C) 2019  Frank Rehberger
+//
+// Licensed under the Apache License, Version 2.0 or MIT License
+
+//! Known failures, resources whose tests are expected to panic
+
+use quote::quote;
+use std::path::{Path, PathBuf};
+use syn::parse::Result;
+use syn::{Error, LitStr};
+
+use crate::resources::Resource;
+use crate::template::slash_path;
+
+/// List of known failures, one resource per line relative to the root directory; empty lines
+/// and lines starting with `#` are skipped
+pub(crate) struct XfailList {
+    /// Absolute path of the list
+    pub(crate) path: PathBuf,
+    entries: Vec<String>,
+}
+
+impl XfailList {
+    /// Read the list, resolved against the root directory; errors are reported at the literal
+    pub(crate) fn read(root_dir: &Path, lit: &LitStr) -> Result<XfailList> {
+        let path = root_dir.join(lit.value());
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            Error::new_spanned(
+                lit,
+                format!("failed to read xfail list {}: {}", path.display(), err),
+            )
+        })?;
+        let entries = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(XfailList { path, entries })
+    }
+
+    /// Return true if the resource is listed
+    pub(crate) fn contains(&self, resource: &Resource) -> bool {
+        let rel_path = slash_path(&resource.rel_path);
+        self.entries.contains(&rel_path)
+    }
+
+    /// Report entries not matching any of the resources, keeping the list up to date
+    pub(crate) fn check_stale(&self, resources: &[Resource], lit: &LitStr) -> Result<()> {
+        let rel_paths: Vec<String> = resources
+            .iter()
+            .map(|resource| slash_path(&resource.rel_path))
+            .collect();
+        match self.entries.iter().find(|entry| !rel_paths.contains(entry)) {
+            Some(entry) => Err(Error::new_spanned(
+                lit,
+                format!(
+                    "{} is listed in {}, but no such resource is tested, remove it from the xfail list",
+                    entry,
+                    self.path.display()
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Statements running the body, expecting it to panic, or to return an error if `fallible`;
+/// if the body succeeds, the test fails asking to remove the resource from the list
+pub(crate) fn xfail_guard(
+    body: proc_macro2::TokenStream,
+    fallible: bool,
+    resource: &Path,
+    list: &Path,
+) -> proc_macro2::TokenStream {
+    let message = format!(
+        "{} unexpectedly passed, remove it from the xfail list {}",
+        slash_path(resource),
+        list.display()
+    );
+    let passed = if fallible {
+        quote! { Ok(result) => result.is_ok() }
+    } else {
+        quote! { Ok(()) => true }
+    };
+    quote! {
+        let passed = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            #body
+        })) {
+            #passed,
+            Err(_) => false,
+        };
+        if passed {
+            panic!(#message);
+        }
+    }
+}